@@ -0,0 +1,98 @@
+//! Counts allocations in a tight boolean logging loop, run with `cargo bench --bench bool_allocations`.
+//!
+//! Compares logging through a boxed byte (what `BooleanEntry` used to do) against `BooleanEntry`
+//! itself, with and without a buffer pool.
+
+use anyhow::Result;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+use wpilog::{
+    entrytypes::{BooleanEntry, BytesEntry, Entry, LogWriter},
+    writer::{TimeProvider, WPILOGWriterBuilder},
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Copy, Clone, Debug)]
+struct NoopTimeProvider {}
+
+impl TimeProvider for NoopTimeProvider {
+    fn get_time(&self) -> u64 {
+        0
+    }
+}
+
+const UPDATES: usize = 1_000_000;
+
+struct Entries {
+    raw: BytesEntry<NoopTimeProvider>,
+    bool: BooleanEntry<NoopTimeProvider>,
+}
+
+fn run(
+    name: &str,
+    pooled: bool,
+    update: impl Fn(&Entries, bool) -> wpilog::error::Result<usize>,
+) -> Result<()> {
+    let mut builder = WPILOGWriterBuilder::new().channel_capacity(1024);
+    if pooled {
+        builder = builder.buffer_pool(1024);
+    }
+    let writer = builder.build(io::sink(), NoopTimeProvider {})?;
+    let entries = Entries {
+        raw: writer.new_bytes_entry("raw".into(), None)?,
+        bool: writer.new_bool_entry("bool".into(), None)?,
+    };
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 0..UPDATES {
+        update(&entries, i % 2 == 0)?;
+    }
+    drop(entries);
+    writer.join()?;
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!(
+        "{name:<24} {:>6.2} allocations/update {:>8.1} ns/update",
+        allocations as f64 / UPDATES as f64,
+        elapsed.as_nanos() as f64 / UPDATES as f64
+    );
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    run("boxed byte", false, |entries, value| {
+        entries.raw.update(Box::new([u8::from(value)]))
+    })?;
+    run("BooleanEntry", false, |entries, value| {
+        entries.bool.update(value)
+    })?;
+    run("BooleanEntry (pooled)", true, |entries, value| {
+        entries.bool.update(value)
+    })?;
+
+    Ok(())
+}
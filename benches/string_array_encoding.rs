@@ -0,0 +1,110 @@
+//! Times encoding `string[]` payloads, run with `cargo bench --bench string_array_encoding`.
+//!
+//! Compares the byte by byte loop `StringArrayEntry` used to have against
+//! [`encode_string_array()`], which copies each string in one go.
+
+use std::{hint::black_box, time::Instant};
+use wpilog::{
+    error::{Result, WpilogError},
+    value::encode_string_array,
+};
+
+/// The old `StringArrayEntry::update_with_timestamp()` encoding, kept as it was (length checks
+/// included) so the comparison is fair.
+fn encode_byte_by_byte(data: &[&str]) -> Result<Box<[u8]>> {
+    let length = 4 + 4 * data.len() + data.iter().map(|string| str::len(string)).sum::<usize>();
+
+    let mut dest = vec![0; length].into_boxed_slice();
+
+    let data_len: u32 = if let Ok(data_len) = data.len().try_into() {
+        data_len
+    } else {
+        return Err(WpilogError::InvalidInput(format!(
+            "Data can have at max {} items",
+            u32::MAX
+        )));
+    };
+
+    let size_encoded = data_len.to_le_bytes();
+    dest[0] = size_encoded[0];
+    dest[1] = size_encoded[1];
+    dest[2] = size_encoded[2];
+    dest[3] = size_encoded[3];
+
+    let mut i = 4;
+    for item in data {
+        let item_len: u32 = if let Ok(item_len) = item.len().try_into() {
+            item_len
+        } else {
+            return Err(WpilogError::InvalidInput(format!(
+                "Strings can be at max {} bytes long",
+                u32::MAX
+            )));
+        };
+
+        let size_encoded = item_len.to_le_bytes();
+        dest[i] = size_encoded[0];
+        i += 1;
+        dest[i] = size_encoded[1];
+        i += 1;
+        dest[i] = size_encoded[2];
+        i += 1;
+        dest[i] = size_encoded[3];
+        i += 1;
+
+        let encoded = item.as_bytes();
+        for byte in encoded {
+            dest[i] = *byte;
+            i += 1;
+        }
+    }
+
+    Ok(dest)
+}
+
+fn run(
+    name: &str,
+    data: &[&str],
+    iterations: usize,
+    encode: impl Fn(&[&str]) -> Result<Box<[u8]>>,
+) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(encode(black_box(data)).unwrap());
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{name:<32} {:>10.1} ns/encode",
+        elapsed.as_nanos() as f64 / iterations as f64
+    );
+}
+
+fn main() {
+    let short = vec!["motor"; 1000];
+    let long_string = "a".repeat(4096);
+    let long = vec![long_string.as_str(); 100];
+
+    for (name, data, iterations) in [
+        ("1000 short strings", &short, 10_000),
+        ("100 4 KiB strings", &long, 1_000),
+    ] {
+        assert_eq!(
+            encode_byte_by_byte(data).unwrap(),
+            encode_string_array(data).unwrap()
+        );
+
+        run(
+            &format!("{name}, byte by byte"),
+            data,
+            iterations,
+            encode_byte_by_byte,
+        );
+        run(
+            &format!("{name}, copied"),
+            data,
+            iterations,
+            encode_string_array,
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::{env, fs, io::Write};
 use wpilog::{
-    entrytypes::Entry,
+    entrytypes::{Entry, LogWriter},
     writer::{TimeProvider, WPILOGWriter},
 };
 
@@ -1,9 +1,6 @@
 use anyhow::Result;
 use std::{env, fs};
-use wpilog::{
-    reader::{PlainRecord, WPILOGReader},
-    Record,
-};
+use wpilog::reader::WPILOGReader;
 
 fn main() -> Result<()> {
     let args = env::args().collect::<Vec<String>>();
@@ -22,7 +19,9 @@ fn main() -> Result<()> {
     let reader = WPILOGReader::new_raw(data)?;
 
     let mut records = 0;
-    for record in reader.map(|item: PlainRecord| -> Record { item.try_into().unwrap() }) {
+    for record in reader.schema().skip_unknown_control(true) {
+        let record = record?;
+
         if records < count {
             dbg!(record);
         }
@@ -0,0 +1,9 @@
+//! Run with `cargo +nightly fuzz run read_all`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = wpilog::reader::read_all_lossy(data);
+});
@@ -0,0 +1,68 @@
+//! The optional CRC32 trailer, see [`WPILOGWriterBuilder::checksum()`](crate::writer::WPILOGWriterBuilder::checksum).
+//!
+//! This extends the standard format, but only by using a normal `int64` entry, so readers that don't
+//! know about it just see one more entry with a single value.
+
+/// Name of the entry holding the checksum.
+pub const ENTRY_NAME: &str = "/.wpilog/crc32";
+/// Type of the entry holding the checksum, the CRC is stored zero extended.
+pub const ENTRY_TYPE: &str = "int64";
+/// Id the writer reserves for the checksum entry.
+pub(crate) const ENTRY_ID: u32 = 1;
+
+/// Lookup table for the reflected IEEE polynomial, the same CRC32 used by zip and png.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        // i < 256 so this never truncates
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+/// A running CRC32, fed everything written after the file header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Crc32 {
+        Crc32(u32::MAX)
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 = TABLE[usize::from(self.0.to_le_bytes()[0] ^ byte)] ^ (self.0 >> 8);
+        }
+    }
+
+    pub(crate) fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+impl std::io::Write for Crc32 {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Comparing which entries two logs have, see [`compare()`].
+
+use std::{collections::BTreeMap, io::Read};
+
+use crate::{error::Result, reader::WPILOGReader, schema::SchemaReader, RecordInfo};
+
+/// How many data records an entry has in each log, see [`LogDiff::count_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountChange {
+    pub a: u64,
+    pub b: u64,
+}
+
+impl CountChange {
+    /// How many more records `b` has, negative if it has fewer.
+    #[must_use]
+    pub fn delta(self) -> i128 {
+        i128::from(self.b) - i128::from(self.a)
+    }
+}
+
+/// The structural differences found by [`compare()`], everything is sorted by entry name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogDiff {
+    /// Entries only in `b`
+    pub added: Vec<String>,
+    /// Entries only in `a`
+    pub removed: Vec<String>,
+    /// Entries in both, but with a different number of data records
+    pub count_changes: BTreeMap<String, CountChange>,
+}
+
+impl LogDiff {
+    /// If both logs have the same entries with the same number of records.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.count_changes.is_empty()
+    }
+}
+
+/// Reads every remaining record of both logs, comparing their entries by name.
+///
+/// An entry counts as being in a log once it's started, even if it never logs anything. Entries
+/// started more than once under the same name are added together, like
+/// [`WPILOGReader::size_breakdown()`]. Values aren't compared.
+pub fn compare<A: Read, B: Read>(
+    a: &mut WPILOGReader<A>,
+    b: &mut WPILOGReader<B>,
+) -> Result<LogDiff> {
+    let a = record_counts(a)?;
+    let mut b = record_counts(b)?;
+    let mut diff = LogDiff::default();
+
+    for (name, a_count) in a {
+        match b.remove(&name) {
+            Some(b_count) if b_count != a_count => {
+                diff.count_changes.insert(
+                    name,
+                    CountChange {
+                        a: a_count,
+                        b: b_count,
+                    },
+                );
+            }
+            Some(_) => {}
+            None => diff.removed.push(name),
+        }
+    }
+    diff.added = b.into_keys().collect();
+
+    Ok(diff)
+}
+
+/// The number of data records of every entry started in the rest of the log.
+fn record_counts<R: Read>(reader: &mut WPILOGReader<R>) -> Result<BTreeMap<String, u64>> {
+    let mut schema = SchemaReader::new(reader.by_ref());
+    let mut counts = BTreeMap::new();
+
+    while let Some(record) = schema.next().transpose()? {
+        let Some(entry) = schema.entry(record.id) else {
+            continue;
+        };
+        let count = counts.entry(entry.name.to_string()).or_insert(0);
+
+        if let RecordInfo::Data(_) = record.info {
+            *count += 1;
+        }
+    }
+
+    Ok(counts)
+}
@@ -1,21 +1,32 @@
-use std::io::Write;
-
-use anyhow::{format_err, Result};
-
-use crate::writer::{RawEntry, TimeProvider, WPILOGWriter};
-
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    error::{Result, WpilogError},
+    export::json_string,
+    le,
+    types::{nt_type_to_wpilog, WpiType},
+    value::encode_string_array,
+    writer::{RawEntry, TimeProvider},
+};
+
+/// A typed entry. Updating it returns the number of payload bytes logged, so bandwidth can be
+/// metered per entry.
 pub trait Entry<T> {
-    fn update(&self, data: T) -> Result<()>;
+    fn update(&self, data: T) -> Result<usize>;
 
-    fn update_with_timestamp(&self, data: T, timestamp: u64) -> Result<()>;
+    fn update_with_timestamp(&self, data: T, timestamp: u64) -> Result<usize>;
 }
 
 macro_rules! new_entry_func {
-    ($name:ident, $type:ident, $type_name:literal) => {
+    ($name:ident, $type:ident, $type_name:expr) => {
         #[doc = "Creates a new [`"]
         #[doc = stringify!($type)]
         #[doc = "`]."]
-        pub fn $name(&self, name: String, metadata: Option<String>) -> Result<$type<T>> {
+        fn $name(&self, name: String, metadata: Option<String>) -> Result<$type<T>> {
             Ok($type(self.make_entry(
                 name,
                 $type_name.to_string(),
@@ -25,46 +36,369 @@ macro_rules! new_entry_func {
     };
 }
 
-impl<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static> WPILOGWriter<T, W> {
-    new_entry_func!(new_bytes_entry, BytesEntry, "raw");
+/// Like [`new_entry_func`], but makes a `_with_unit` constructor that takes a unit instead of the
+/// metadata, see [`unit_metadata()`].
+macro_rules! new_unit_entry_func {
+    ($name:ident, $type:ident, $type_name:expr) => {
+        #[doc = "Creates a new [`"]
+        #[doc = stringify!($type)]
+        #[doc = "`] measured in `unit`, with metadata like `{\"unit\":\"meters\"}` (see [`unit_metadata()`])."]
+        #[cfg(feature = "serde_json")]
+        fn $name(&self, name: String, unit: Option<&str>) -> Result<$type<T>> {
+            Ok($type(self.make_entry(
+                name,
+                $type_name.to_string(),
+                unit.map(unit_metadata).unwrap_or_default(),
+            )?))
+        }
+    };
+}
+
+/// Metadata saying an entry is measured in `unit`, like `{"unit":"meters"}`, for tools like `AdvantageScope`.
+///
+/// The `_with_unit` constructors of [`LogWriter`] use this, like
+/// `writer.new_f64_entry_with_unit(name, Some("meters"))`. For other metadata along with the unit,
+/// build the JSON yourself and pass it as the metadata of a normal constructor.
+#[cfg(feature = "serde_json")]
+#[must_use]
+pub fn unit_metadata(unit: &str) -> String {
+    serde_json::json!({ "unit": unit }).to_string()
+}
+
+/// Everything that can hand out entries, implemented by both
+/// [`WPILOGWriter`](crate::writer::WPILOGWriter) and [`NullWriter`](crate::writer::NullWriter)
+/// so code can log without caring if logging is actually enabled.
+pub trait LogWriter<T: TimeProvider + Clone + Send + Sync> {
+    /// Don't use this unless you know what you are doing.
+    ///
+    /// This method returns a [`RawEntry`], which requires you enforce that data is given the right format.
+    ///
+    /// Messing up data formatting can result in tools being unable to interpret what your logged values actually mean.
+    fn make_entry(&self, name: String, r#type: String, metadata: String) -> Result<RawEntry<T>>;
+
+    /// The time provider entries made by this writer use.
+    fn time_provider(&self) -> &T;
+
+    new_entry_func!(new_bytes_entry, BytesEntry, WpiType::Raw);
+    new_entry_func!(new_u8_entry, U8Entry, WpiType::Raw);
+
+    new_entry_func!(new_bool_entry, BooleanEntry, WpiType::Boolean);
+    new_entry_func!(new_i64_entry, I64Entry, WpiType::Int64);
+    new_entry_func!(new_f32_entry, F32Entry, WpiType::Float);
+    new_entry_func!(new_f64_entry, F64Entry, WpiType::Double);
+    new_entry_func!(new_string_entry, StringEntry, WpiType::String);
+    new_entry_func!(new_json_entry, JsonEntry, WpiType::Json);
+    new_entry_func!(new_msgpack_entry, MsgpackEntry, WpiType::Msgpack);
+
+    new_entry_func!(new_i8_entry, I8Entry, WpiType::Int64);
+    new_entry_func!(new_i16_entry, I16Entry, WpiType::Int64);
+    new_entry_func!(new_i32_entry, I32Entry, WpiType::Int64);
+    new_entry_func!(new_u16_entry, U16Entry, WpiType::Int64);
+    new_entry_func!(new_u32_entry, U32Entry, WpiType::Int64);
+
+    new_entry_func!(
+        new_bool_array_entry,
+        BooleanArrayEntry,
+        WpiType::BooleanArray
+    );
+    new_entry_func!(new_i64_array_entry, I64ArrayEntry, WpiType::Int64Array);
+    new_entry_func!(new_f32_array_entry, F32ArrayEntry, WpiType::FloatArray);
+    new_entry_func!(new_f64_array_entry, F64ArrayEntry, WpiType::DoubleArray);
+    new_entry_func!(
+        new_string_array_entry,
+        StringArrayEntry,
+        WpiType::StringArray
+    );
+
+    new_entry_func!(
+        new_packed_bool_array_entry,
+        PackedBooleanArrayEntry,
+        PACKED_BOOLEAN_ARRAY_TYPE
+    );
+
+    new_unit_entry_func!(new_i64_entry_with_unit, I64Entry, WpiType::Int64);
+    new_unit_entry_func!(new_f32_entry_with_unit, F32Entry, WpiType::Float);
+    new_unit_entry_func!(new_f64_entry_with_unit, F64Entry, WpiType::Double);
+
+    new_unit_entry_func!(new_i8_entry_with_unit, I8Entry, WpiType::Int64);
+    new_unit_entry_func!(new_i16_entry_with_unit, I16Entry, WpiType::Int64);
+    new_unit_entry_func!(new_i32_entry_with_unit, I32Entry, WpiType::Int64);
+    new_unit_entry_func!(new_u16_entry_with_unit, U16Entry, WpiType::Int64);
+    new_unit_entry_func!(new_u32_entry_with_unit, U32Entry, WpiType::Int64);
+
+    new_unit_entry_func!(
+        new_i64_array_entry_with_unit,
+        I64ArrayEntry,
+        WpiType::Int64Array
+    );
+    new_unit_entry_func!(
+        new_f32_array_entry_with_unit,
+        F32ArrayEntry,
+        WpiType::FloatArray
+    );
+    new_unit_entry_func!(
+        new_f64_array_entry_with_unit,
+        F64ArrayEntry,
+        WpiType::DoubleArray
+    );
+
+    /// Logs `schema` to the `/.schema/{name}` entry, for example `name = "struct:Pose2d"` with
+    /// `type_str = "structschema"`.
+    ///
+    /// The schema is queued before this returns, so it's in the log before any struct or proto
+    /// entry made afterwards (on any thread).
+    fn register_schema(&self, name: &str, type_str: &str, schema: &[u8]) -> Result<()> {
+        let entry = self.make_entry(
+            format!("/.schema/{name}"),
+            type_str.to_string(),
+            String::new(),
+        )?;
+        entry.log_data(schema.into())?;
+
+        Ok(())
+    }
 
-    new_entry_func!(new_bool_entry, BooleanEntry, "boolean");
-    new_entry_func!(new_i64_entry, I64Entry, "int64");
-    new_entry_func!(new_f32_entry, F32Entry, "float");
-    new_entry_func!(new_f64_entry, F64Entry, "double");
-    new_entry_func!(new_string_entry, StringEntry, "string");
+    /// Like [`LogWriter::register_schema()`], but does nothing if `name` is already registered.
+    /// Returns if it was logged.
+    fn register_schema_once(&self, name: &str, type_str: &str, schema: &[u8]) -> Result<bool> {
+        if self.schema_registered(name) {
+            return Ok(false);
+        }
+
+        self.register_schema(name, type_str, schema)?;
+        Ok(true)
+    }
+
+    /// Registers the `struct:{struct_name}` schema, along with every struct schema it references
+    /// (as `(struct_name, schema)`), so tools like `AdvantageScope` can decode nested structs.
+    ///
+    /// The dependencies are logged first, in order. Any schema that's already registered, by an
+    /// earlier entry or earlier in `dependencies`, isn't logged again.
+    fn register_struct_schema(
+        &self,
+        struct_name: &str,
+        schema: &[u8],
+        dependencies: &[(&str, &[u8])],
+    ) -> Result<()> {
+        for (name, schema) in dependencies.iter().chain([&(struct_name, schema)]) {
+            self.register_schema_once(&format!("struct:{name}"), "structschema", schema)?;
+        }
+
+        Ok(())
+    }
+
+    /// If a schema called `name` has been [registered](LogWriter::register_schema()).
+    ///
+    /// Writers that don't keep track always say yes.
+    fn schema_registered(&self, name: &str) -> bool {
+        let _ = name;
+        true
+    }
+
+    /// Creates a [`StructEntry`] for `struct:{struct_name}`, whose schema must already be registered.
+    fn new_struct_entry(
+        &self,
+        name: String,
+        struct_name: &str,
+        metadata: Option<String>,
+    ) -> Result<StructEntry<T>> {
+        let schema = format!("struct:{struct_name}");
+        if !self.schema_registered(&schema) {
+            return Err(WpilogError::InvalidInput(format!(
+                "Schema {schema} hasn't been registered"
+            )));
+        }
+
+        Ok(StructEntry(self.make_entry(
+            name,
+            WpiType::Struct(struct_name.into()).to_string(),
+            metadata.unwrap_or_default(),
+        )?))
+    }
+
+    /// Creates a [`StructEntry`] for `struct:{struct_name}[]`, whose schema must already be registered.
+    fn new_struct_array_entry(
+        &self,
+        name: String,
+        struct_name: &str,
+        metadata: Option<String>,
+    ) -> Result<StructEntry<T>> {
+        let schema = format!("struct:{struct_name}");
+        if !self.schema_registered(&schema) {
+            return Err(WpilogError::InvalidInput(format!(
+                "Schema {schema} hasn't been registered"
+            )));
+        }
 
-    new_entry_func!(new_bool_array_entry, BooleanArrayEntry, "boolean[]");
-    new_entry_func!(new_i64_array_entry, I64ArrayEntry, "int64[]");
-    new_entry_func!(new_f32_array_entry, F32ArrayEntry, "float[]");
-    new_entry_func!(new_f64_array_entry, F64ArrayEntry, "double[]");
-    new_entry_func!(new_string_array_entry, StringArrayEntry, "string[]");
+        Ok(StructEntry(self.make_entry(
+            name,
+            WpiType::StructArray(struct_name.into()).to_string(),
+            metadata.unwrap_or_default(),
+        )?))
+    }
+
+    /// Creates a [`PodStructEntry`] for `struct:{struct_name}`, registering `schema` (as a
+    /// `structschema`) first if it hasn't been already.
+    ///
+    /// Values are logged as their bytes in memory, which only matches WPILOG's little endian struct
+    /// layout on little endian targets, so this errors on big endian ones. `V` also has to be laid
+    /// out exactly like the schema says, which generally means `#[repr(C)]` with no padding.
+    #[cfg(feature = "bytemuck")]
+    fn new_pod_struct_entry<V: bytemuck::Pod>(
+        &self,
+        name: String,
+        struct_name: &str,
+        schema: &[u8],
+        metadata: Option<String>,
+    ) -> Result<PodStructEntry<T, V>> {
+        if cfg!(target_endian = "big") {
+            return Err(WpilogError::InvalidInput(
+                "Logging structs from memory needs a little endian target".into(),
+            ));
+        }
+
+        self.register_schema_once(&format!("struct:{struct_name}"), "structschema", schema)?;
+
+        Ok(PodStructEntry {
+            entry: self.new_struct_entry(name, struct_name, metadata)?,
+            value: PhantomData,
+        })
+    }
+
+    /// Creates a [`ProtoEntry`] for `proto:{message_name}`, whose schema must already be registered.
+    fn new_proto_entry(
+        &self,
+        name: String,
+        message_name: &str,
+        metadata: Option<String>,
+    ) -> Result<ProtoEntry<T>> {
+        let schema = format!("proto:{message_name}");
+        if !self.schema_registered(&schema) {
+            return Err(WpilogError::InvalidInput(format!(
+                "Schema {schema} hasn't been registered"
+            )));
+        }
+
+        Ok(ProtoEntry(self.make_entry(
+            name,
+            WpiType::Proto(message_name.into()).to_string(),
+            metadata.unwrap_or_default(),
+        )?))
+    }
+
+    /// Creates an [`NtEntry`] for a `NetworkTables` topic, with the type converted by
+    /// [`nt_type_to_wpilog()`](crate::types::nt_type_to_wpilog).
+    ///
+    /// Topic properties are usually logged as the metadata.
+    fn new_nt_entry(
+        &self,
+        topic: String,
+        nt_type: &str,
+        metadata: Option<String>,
+    ) -> Result<NtEntry<T>> {
+        Ok(NtEntry(self.make_entry(
+            topic,
+            nt_type_to_wpilog(nt_type).to_string(),
+            metadata.unwrap_or_default(),
+        )?))
+    }
+
+    /// Creates an [`EnumEntry`], an `int64` entry whose metadata maps each value to its label, like
+    /// `{"labels":{"0":"Disabled","1":"Auto"}}`, so viewers can show names instead of numbers.
+    fn new_enum_entry<V: Into<i64>>(
+        &self,
+        name: String,
+        labels: &[(i64, &str)],
+    ) -> Result<EnumEntry<T, V>> {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(value, label)| format!("\"{value}\":{}", json_string(label)))
+            .collect();
+
+        Ok(EnumEntry {
+            entry: self.make_entry(
+                name,
+                WpiType::Int64.to_string(),
+                format!("{{\"labels\":{{{}}}}}", labels.join(",")),
+            )?,
+            value: PhantomData,
+        })
+    }
+
+    /// Creates a [`MatrixEntry`], a `double[]` entry for a flattened matrix (or any N dimensional
+    /// array) with its shape in the metadata, like `{"shape":[3,3]}`. Read it back with
+    /// [`EntryInfo::shape()`](crate::schema::EntryInfo::shape).
+    fn new_matrix_entry(&self, name: String, shape: &[usize]) -> Result<MatrixEntry<T>> {
+        let dims: Vec<String> = shape.iter().map(usize::to_string).collect();
+
+        Ok(MatrixEntry {
+            entry: self.make_entry(
+                name,
+                WpiType::DoubleArray.to_string(),
+                format!("{{\"shape\":[{}]}}", dims.join(",")),
+            )?,
+            len: shape.iter().product(),
+        })
+    }
+
+    /// Creates a [`CustomEntry`] with any type string, which logs whatever `encode` turns a value into.
+    ///
+    /// It's up to `encode` to produce data that matches `type_str`.
+    fn new_entry_as<V, F: Fn(V) -> Box<[u8]>>(
+        &self,
+        name: String,
+        type_str: String,
+        metadata: Option<String>,
+        encode: F,
+    ) -> Result<CustomEntry<T, V, F>> {
+        Ok(CustomEntry {
+            entry: self.make_entry(name, type_str, metadata.unwrap_or_default())?,
+            encode,
+            value: PhantomData,
+        })
+    }
+}
+
+/// Entries that log through a single [`RawEntry`], which wrappers like [`NullableEntry`] use to log
+/// things the typed entry can't.
+pub trait RawBacked {
+    type Time: TimeProvider + Clone + Send + Sync;
+
+    fn raw_entry(&self) -> &RawEntry<Self::Time>;
 }
 
 macro_rules! make_entry_type {
     ($name:ident) => {
+        #[derive(Clone)]
         pub struct $name<T: TimeProvider + Clone + Send + Sync>(RawEntry<T>);
+
+        impl<T: TimeProvider + Clone + Send + Sync> RawBacked for $name<T> {
+            type Time = T;
+
+            fn raw_entry(&self) -> &RawEntry<T> {
+                &self.0
+            }
+        }
     };
 }
 
 macro_rules! update_fn {
     ($type:ty) => {
-        fn update(&self, data: $type) -> Result<()> {
-            self.update_with_timestamp(data, self.0.time_provider.get_time())
+        fn update(&self, data: $type) -> Result<usize> {
+            self.update_with_timestamp(data, self.0.time_provider().get_time())
         }
     };
 }
 
 macro_rules! full_entry_type {
-    ($name:ident, $type:ty) => {
+    ($name:ident, $type:ty, $encode:path) => {
         make_entry_type!($name);
 
         impl<T: TimeProvider + Clone + Send + Sync> Entry<$type> for $name<T> {
             update_fn!($type);
 
-            fn update_with_timestamp(&self, data: $type, timestamp: u64) -> Result<()> {
-                self.0
-                    .log_data_with_timestamp(Box::new(data.to_le_bytes()), timestamp)
+            fn update_with_timestamp(&self, data: $type, timestamp: u64) -> Result<usize> {
+                self.0.log_slice_with_timestamp(&$encode(data), timestamp)
             }
         }
     };
@@ -72,18 +406,41 @@ macro_rules! full_entry_type {
 
 // PRIMITIVES:
 
-full_entry_type!(I64Entry, i64);
-full_entry_type!(F32Entry, f32);
-full_entry_type!(F64Entry, f64);
+full_entry_type!(I64Entry, i64, le::encode_i64);
+full_entry_type!(F32Entry, f32, le::encode_f32);
+full_entry_type!(F64Entry, f64, le::encode_f64);
+
+// Integers smaller than i64 are widened since int64 is the only integer type WPILOG has
+macro_rules! widening_entry_type {
+    ($name:ident, $type:ty) => {
+        make_entry_type!($name);
+
+        impl<T: TimeProvider + Clone + Send + Sync> Entry<$type> for $name<T> {
+            update_fn!($type);
+
+            fn update_with_timestamp(&self, data: $type, timestamp: u64) -> Result<usize> {
+                self.0
+                    .log_slice_with_timestamp(&le::encode_i64(i64::from(data)), timestamp)
+            }
+        }
+    };
+}
+
+widening_entry_type!(I8Entry, i8);
+widening_entry_type!(I16Entry, i16);
+widening_entry_type!(I32Entry, i32);
+widening_entry_type!(U16Entry, u16);
+widening_entry_type!(U32Entry, u32);
 
 make_entry_type!(BooleanEntry);
 
 impl<T: TimeProvider + Clone + Send + Sync> Entry<bool> for BooleanEntry<T> {
     update_fn!(bool);
 
-    fn update_with_timestamp(&self, data: bool, timestamp: u64) -> Result<()> {
+    fn update_with_timestamp(&self, data: bool, timestamp: u64) -> Result<usize> {
+        // Logged from the stack, a boxed single byte is a lot of overhead for fast toggling entries
         self.0
-            .log_data_with_timestamp(Box::new([u8::from(data)]), timestamp)
+            .log_slice_with_timestamp(&[u8::from(data)], timestamp)
     }
 }
 
@@ -92,29 +449,361 @@ make_entry_type!(BytesEntry);
 impl<T: TimeProvider + Clone + Send + Sync> Entry<Box<[u8]>> for BytesEntry<T> {
     update_fn!(Box<[u8]>);
 
-    fn update_with_timestamp(&self, data: Box<[u8]>, timestamp: u64) -> Result<()> {
+    fn update_with_timestamp(&self, data: Box<[u8]>, timestamp: u64) -> Result<usize> {
+        self.0.log_data_with_timestamp(data, timestamp)
+    }
+}
+
+make_entry_type!(U8Entry);
+
+/// A status byte or character, logged as a single byte `raw` payload. Read it back with
+/// [`decode_u8()`](crate::value::decode_u8).
+impl<T: TimeProvider + Clone + Send + Sync> Entry<u8> for U8Entry<T> {
+    update_fn!(u8);
+
+    fn update_with_timestamp(&self, data: u8, timestamp: u64) -> Result<usize> {
+        self.0.log_slice_with_timestamp(&[data], timestamp)
+    }
+}
+
+make_entry_type!(MsgpackEntry);
+
+/// Takes the already serialized `MessagePack`
+impl<T: TimeProvider + Clone + Send + Sync> Entry<Box<[u8]>> for MsgpackEntry<T> {
+    update_fn!(Box<[u8]>);
+
+    fn update_with_timestamp(&self, data: Box<[u8]>, timestamp: u64) -> Result<usize> {
         self.0.log_data_with_timestamp(data, timestamp)
     }
 }
 
+make_entry_type!(StructEntry);
+
+/// Takes the already serialized struct (or array of structs)
+impl<T: TimeProvider + Clone + Send + Sync> Entry<&[u8]> for StructEntry<T> {
+    update_fn!(&[u8]);
+
+    fn update_with_timestamp(&self, data: &[u8], timestamp: u64) -> Result<usize> {
+        self.0.log_data_with_timestamp(data.into(), timestamp)
+    }
+}
+
+/// An enum logged as its `int64` value, see [`LogWriter::new_enum_entry()`].
+#[derive(Clone)]
+pub struct EnumEntry<T: TimeProvider + Clone + Send + Sync, V> {
+    entry: RawEntry<T>,
+    value: PhantomData<fn(V)>,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, V: Into<i64>> Entry<V> for EnumEntry<T, V> {
+    fn update(&self, data: V) -> Result<usize> {
+        self.update_with_timestamp(data, self.entry.time_provider().get_time())
+    }
+
+    fn update_with_timestamp(&self, data: V, timestamp: u64) -> Result<usize> {
+        self.entry
+            .log_slice_with_timestamp(&le::encode_i64(data.into()), timestamp)
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, V> RawBacked for EnumEntry<T, V> {
+    type Time = T;
+
+    fn raw_entry(&self) -> &RawEntry<T> {
+        &self.entry
+    }
+}
+
+/// Wraps an entry to log `Option`s, `None` is logged as a record with no data (see
+/// [`RawEntry::log_empty()`]).
+///
+/// This is meant for scalar entries like [`F64Entry`] and [`BooleanEntry`], which
+/// [`DecodedValue::decode()`](crate::value::DecodedValue::decode) reads back as
+/// [`DecodedValue::Empty`](crate::value::DecodedValue::Empty) for `None`. For types where no data
+/// is a normal value, like strings, `None` can't be told apart from an empty value.
+#[derive(Clone)]
+pub struct NullableEntry<E>(pub E);
+
+impl<V, E: Entry<V> + RawBacked> Entry<Option<V>> for NullableEntry<E> {
+    fn update(&self, data: Option<V>) -> Result<usize> {
+        match data {
+            Some(data) => self.0.update(data),
+            None => self.0.raw_entry().log_empty(),
+        }
+    }
+
+    fn update_with_timestamp(&self, data: Option<V>, timestamp: u64) -> Result<usize> {
+        match data {
+            Some(data) => self.0.update_with_timestamp(data, timestamp),
+            None => self.0.raw_entry().log_slice_with_timestamp(&[], timestamp),
+        }
+    }
+}
+
+/// A struct entry that logs a plain old data type's bytes as-is, see
+/// [`LogWriter::new_pod_struct_entry()`].
+#[cfg(feature = "bytemuck")]
+#[derive(Clone)]
+pub struct PodStructEntry<T: TimeProvider + Clone + Send + Sync, V: bytemuck::Pod> {
+    entry: StructEntry<T>,
+    value: PhantomData<fn(&V)>,
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: TimeProvider + Clone + Send + Sync, V: bytemuck::Pod> Entry<&V> for PodStructEntry<T, V> {
+    fn update(&self, data: &V) -> Result<usize> {
+        self.entry.update(bytemuck::bytes_of(data))
+    }
+
+    fn update_with_timestamp(&self, data: &V, timestamp: u64) -> Result<usize> {
+        self.entry
+            .update_with_timestamp(bytemuck::bytes_of(data), timestamp)
+    }
+}
+
+make_entry_type!(ProtoEntry);
+
+/// Takes the already serialized message
+impl<T: TimeProvider + Clone + Send + Sync> Entry<&[u8]> for ProtoEntry<T> {
+    update_fn!(&[u8]);
+
+    fn update_with_timestamp(&self, data: &[u8], timestamp: u64) -> Result<usize> {
+        self.0.log_data_with_timestamp(data.into(), timestamp)
+    }
+}
+
+make_entry_type!(NtEntry);
+
+/// Takes a value already serialized the way WPILOG stores its type
+impl<T: TimeProvider + Clone + Send + Sync> Entry<&[u8]> for NtEntry<T> {
+    update_fn!(&[u8]);
+
+    fn update_with_timestamp(&self, data: &[u8], timestamp: u64) -> Result<usize> {
+        self.0.log_slice_with_timestamp(data, timestamp)
+    }
+}
+
 make_entry_type!(StringEntry);
 
 impl<T: TimeProvider + Clone + Send + Sync> Entry<String> for StringEntry<T> {
     update_fn!(String);
 
-    fn update_with_timestamp(&self, data: String, timestamp: u64) -> Result<()> {
+    fn update_with_timestamp(&self, data: String, timestamp: u64) -> Result<usize> {
+        self.update_str_with_timestamp(data, timestamp)
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> StringEntry<T> {
+    /// Same as [`Entry::update()`], but takes a `&str`, `Cow<str>` or anything else that derefs to
+    /// one, without making a `String` first.
+    pub fn update_str(&self, data: impl AsRef<str>) -> Result<usize> {
+        self.update_str_with_timestamp(data, self.0.time_provider().get_time())
+    }
+
+    /// Same as [`Entry::update_with_timestamp()`], but takes a `&str`, `Cow<str>` or anything else
+    /// that derefs to one. It's copied straight into the record.
+    // Taking it by value is what lets a `String` or `Cow` be passed in directly
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn update_str_with_timestamp(
+        &self,
+        data: impl AsRef<str>,
+        timestamp: u64,
+    ) -> Result<usize> {
+        self.0
+            .log_slice_with_timestamp(data.as_ref().as_bytes(), timestamp)
+    }
+}
+
+make_entry_type!(JsonEntry);
+
+/// Takes the already serialized JSON text
+impl<T: TimeProvider + Clone + Send + Sync> Entry<String> for JsonEntry<T> {
+    update_fn!(String);
+
+    fn update_with_timestamp(&self, data: String, timestamp: u64) -> Result<usize> {
         self.0
             .log_data_with_timestamp(data.into_boxed_str().into(), timestamp)
     }
 }
 
+/// An entry with a custom type string and encoding, see [`LogWriter::new_entry_as()`].
+#[derive(Clone)]
+pub struct CustomEntry<T: TimeProvider + Clone + Send + Sync, V, F: Fn(V) -> Box<[u8]>> {
+    entry: RawEntry<T>,
+    encode: F,
+    value: PhantomData<fn(V)>,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, V, F: Fn(V) -> Box<[u8]>> Entry<V>
+    for CustomEntry<T, V, F>
+{
+    fn update(&self, data: V) -> Result<usize> {
+        self.update_with_timestamp(data, self.entry.time_provider().get_time())
+    }
+
+    fn update_with_timestamp(&self, data: V, timestamp: u64) -> Result<usize> {
+        self.entry
+            .log_data_with_timestamp((self.encode)(data), timestamp)
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, V, F: Fn(V) -> Box<[u8]>> RawBacked
+    for CustomEntry<T, V, F>
+{
+    type Time = T;
+
+    fn raw_entry(&self) -> &RawEntry<T> {
+        &self.entry
+    }
+}
+
+/// Wraps a float entry to reject NaN and infinities instead of logging them.
+///
+/// WPILOG itself has no problem with non-finite values, this is only for pipelines that can't handle them.
+#[derive(Clone)]
+pub struct FiniteEntry<E>(pub E);
+
+macro_rules! finite_entry_impl {
+    ($type:ty) => {
+        impl<E: Entry<$type>> Entry<$type> for FiniteEntry<E> {
+            fn update(&self, data: $type) -> Result<usize> {
+                if !data.is_finite() {
+                    return Err(WpilogError::InvalidInput(format!(
+                        "Refusing to log non-finite value {data}"
+                    )));
+                }
+
+                self.0.update(data)
+            }
+
+            fn update_with_timestamp(&self, data: $type, timestamp: u64) -> Result<usize> {
+                if !data.is_finite() {
+                    return Err(WpilogError::InvalidInput(format!(
+                        "Refusing to log non-finite value {data}"
+                    )));
+                }
+
+                self.0.update_with_timestamp(data, timestamp)
+            }
+        }
+    };
+    ($type:ty, array) => {
+        impl<E: for<'a> Entry<&'a [$type]>> Entry<&[$type]> for FiniteEntry<E> {
+            fn update(&self, data: &[$type]) -> Result<usize> {
+                if let Some(item) = data.iter().find(|item| !item.is_finite()) {
+                    return Err(WpilogError::InvalidInput(format!(
+                        "Refusing to log non-finite value {item}"
+                    )));
+                }
+
+                self.0.update(data)
+            }
+
+            fn update_with_timestamp(&self, data: &[$type], timestamp: u64) -> Result<usize> {
+                if let Some(item) = data.iter().find(|item| !item.is_finite()) {
+                    return Err(WpilogError::InvalidInput(format!(
+                        "Refusing to log non-finite value {item}"
+                    )));
+                }
+
+                self.0.update_with_timestamp(data, timestamp)
+            }
+        }
+    };
+}
+
+impl<E: RawBacked> RawBacked for FiniteEntry<E> {
+    type Time = E::Time;
+
+    fn raw_entry(&self) -> &RawEntry<E::Time> {
+        self.0.raw_entry()
+    }
+}
+
+finite_entry_impl!(f32);
+finite_entry_impl!(f64);
+finite_entry_impl!(f32, array);
+finite_entry_impl!(f64, array);
+
+/// Wraps an entry to log at most once per `min_interval`, updates that come sooner are dropped.
+///
+/// Time comes from the entry's own [`TimeProvider`] (or the timestamp given), so it's throttled in
+/// log time like everything else. Dropped updates return `Ok(0)` since nothing was logged.
+pub struct ThrottledEntry<E> {
+    entry: E,
+    /// In microseconds, like timestamps
+    min_interval: u64,
+    /// Timestamp of the last update that was logged, [`u64::MAX`] before the first
+    last: AtomicU64,
+}
+
+impl<E> ThrottledEntry<E> {
+    pub fn new(entry: E, min_interval: Duration) -> Self {
+        ThrottledEntry {
+            entry,
+            min_interval: u64::try_from(min_interval.as_micros()).unwrap_or(u64::MAX),
+            last: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// The wrapped entry.
+    pub fn inner(&self) -> &E {
+        &self.entry
+    }
+
+    /// Claims `timestamp` as the last logged update if it's far enough after the previous one.
+    fn claim(&self, timestamp: u64) -> bool {
+        let mut last = self.last.load(Ordering::Relaxed);
+
+        loop {
+            // Timestamps going backwards count as too soon
+            if last != u64::MAX && timestamp.saturating_sub(last) < self.min_interval {
+                return false;
+            }
+
+            match self.last.compare_exchange_weak(
+                last,
+                timestamp,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => last = actual,
+            }
+        }
+    }
+}
+
+impl<E: RawBacked> RawBacked for ThrottledEntry<E> {
+    type Time = E::Time;
+
+    fn raw_entry(&self) -> &RawEntry<E::Time> {
+        self.entry.raw_entry()
+    }
+}
+
+impl<V, E: Entry<V> + RawBacked> Entry<V> for ThrottledEntry<E> {
+    fn update(&self, data: V) -> Result<usize> {
+        let timestamp = self.entry.raw_entry().time_provider().get_time();
+        self.update_with_timestamp(data, timestamp)
+    }
+
+    fn update_with_timestamp(&self, data: V, timestamp: u64) -> Result<usize> {
+        if !self.claim(timestamp) {
+            return Ok(0);
+        }
+
+        self.entry.update_with_timestamp(data, timestamp)
+    }
+}
+
 // PRIMITIVE ARRAYS:
 make_entry_type!(BooleanArrayEntry);
 
 impl<T: TimeProvider + Clone + Send + Sync> Entry<&[bool]> for BooleanArrayEntry<T> {
     update_fn!(&[bool]);
 
-    fn update_with_timestamp(&self, data: &[bool], timestamp: u64) -> Result<()> {
+    fn update_with_timestamp(&self, data: &[bool], timestamp: u64) -> Result<usize> {
         let mut tmp = vec![0; data.len()].into_boxed_slice();
 
         // TODO: There has to be a better way to do this
@@ -131,23 +820,9 @@ make_entry_type!(I64ArrayEntry);
 impl<T: TimeProvider + Clone + Send + Sync> Entry<&[i64]> for I64ArrayEntry<T> {
     update_fn!(&[i64]);
 
-    fn update_with_timestamp(&self, data: &[i64], timestamp: u64) -> Result<()> {
-        let mut dest = vec![0; data.len() * 4].into_boxed_slice();
-
-        let mut i = 0;
-        for item in data {
-            let encoded = item.to_le_bytes();
-            dest[i] = encoded[0];
-            i += 1;
-            dest[i] = encoded[1];
-            i += 1;
-            dest[i] = encoded[2];
-            i += 1;
-            dest[i] = encoded[3];
-            i += 1;
-        }
-
-        self.0.log_data_with_timestamp(dest, timestamp)
+    fn update_with_timestamp(&self, data: &[i64], timestamp: u64) -> Result<usize> {
+        self.0
+            .log_data_with_timestamp(le::encode_slice(data, le::encode_i64), timestamp)
     }
 }
 
@@ -156,23 +831,9 @@ make_entry_type!(F32ArrayEntry);
 impl<T: TimeProvider + Clone + Send + Sync> Entry<&[f32]> for F32ArrayEntry<T> {
     update_fn!(&[f32]);
 
-    fn update_with_timestamp(&self, data: &[f32], timestamp: u64) -> Result<()> {
-        let mut dest = vec![0; data.len() * 4].into_boxed_slice();
-
-        let mut i = 0;
-        for item in data {
-            let encoded = item.to_le_bytes();
-            dest[i] = encoded[0];
-            i += 1;
-            dest[i] = encoded[1];
-            i += 1;
-            dest[i] = encoded[2];
-            i += 1;
-            dest[i] = encoded[3];
-            i += 1;
-        }
-
-        self.0.log_data_with_timestamp(dest, timestamp)
+    fn update_with_timestamp(&self, data: &[f32], timestamp: u64) -> Result<usize> {
+        self.0
+            .log_data_with_timestamp(le::encode_slice(data, le::encode_f32), timestamp)
     }
 }
 
@@ -181,74 +842,164 @@ make_entry_type!(F64ArrayEntry);
 impl<T: TimeProvider + Clone + Send + Sync> Entry<&[f64]> for F64ArrayEntry<T> {
     update_fn!(&[f64]);
 
-    fn update_with_timestamp(&self, data: &[f64], timestamp: u64) -> Result<()> {
-        let mut dest = vec![0; data.len() * 8].into_boxed_slice();
-
-        let mut i = 0;
-        for item in data {
-            let encoded = item.to_le_bytes();
-            dest[i] = encoded[0];
-            i += 1;
-            dest[i] = encoded[1];
-            i += 1;
-            dest[i] = encoded[2];
-            i += 1;
-            dest[i] = encoded[3];
-            i += 1;
-            dest[i] = encoded[4];
-            i += 1;
-            dest[i] = encoded[5];
-            i += 1;
-            dest[i] = encoded[6];
-            i += 1;
-            dest[i] = encoded[7];
-            i += 1;
+    fn update_with_timestamp(&self, data: &[f64], timestamp: u64) -> Result<usize> {
+        self.0
+            .log_data_with_timestamp(le::encode_slice(data, le::encode_f64), timestamp)
+    }
+}
+
+/// A flattened `double[]` matrix, see [`LogWriter::new_matrix_entry()`].
+///
+/// Values are logged in whatever order they're given, row major is the usual choice.
+#[derive(Clone)]
+pub struct MatrixEntry<T: TimeProvider + Clone + Send + Sync> {
+    entry: RawEntry<T>,
+    /// How many values the shape has room for
+    len: usize,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> RawBacked for MatrixEntry<T> {
+    type Time = T;
+
+    fn raw_entry(&self) -> &RawEntry<T> {
+        &self.entry
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> Entry<&[f64]> for MatrixEntry<T> {
+    fn update(&self, data: &[f64]) -> Result<usize> {
+        self.update_with_timestamp(data, self.entry.time_provider().get_time())
+    }
+
+    /// # Errors
+    /// [`WpilogError::InvalidInput`] if `data` doesn't have exactly as many values as the shape.
+    fn update_with_timestamp(&self, data: &[f64], timestamp: u64) -> Result<usize> {
+        if data.len() != self.len {
+            return Err(WpilogError::InvalidInput(format!(
+                "Matrix needs {} values, got {}",
+                self.len,
+                data.len()
+            )));
         }
 
-        self.0.log_data_with_timestamp(dest, timestamp)
+        self.entry
+            .log_data_with_timestamp(le::encode_slice(data, le::encode_f64), timestamp)
     }
 }
 
+/// Adds `update_iter` for logging an array straight from an iterator, encoding each item straight
+/// into the record without collecting them first
+macro_rules! update_iter_impl {
+    ($name:ident, $type:ty, $size:expr, $encode:expr) => {
+        impl<T: TimeProvider + Clone + Send + Sync> $name<T> {
+            /// Same as [`Entry::update()`], but takes the array as an iterator.
+            pub fn update_iter<I: IntoIterator<Item = $type>>(&self, iter: I) -> Result<usize>
+            where
+                I::IntoIter: ExactSizeIterator,
+            {
+                self.update_iter_with_timestamp(iter, self.0.time_provider().get_time())
+            }
+
+            /// Same as [`Entry::update_with_timestamp()`], but takes the array as an iterator.
+            pub fn update_iter_with_timestamp<I: IntoIterator<Item = $type>>(
+                &self,
+                iter: I,
+                timestamp: u64,
+            ) -> Result<usize>
+            where
+                I::IntoIter: ExactSizeIterator,
+            {
+                let iter = iter.into_iter();
+                let len = iter.len();
+                let Some(size) = len.checked_mul($size) else {
+                    return Err(WpilogError::InvalidInput(format!(
+                        "Data can have at max {} items",
+                        usize::MAX / $size
+                    )));
+                };
+
+                // Taking `len` items means an iterator that has more can't overrun the record, one
+                // that has fewer is refused since the payload comes up short
+                self.0.log_encoded_with_timestamp(size, timestamp, |buf| {
+                    for item in iter.take(len) {
+                        buf.extend_from_slice(&$encode(item));
+                    }
+                })
+            }
+        }
+    };
+}
+
+update_iter_impl!(BooleanArrayEntry, bool, 1, |item| [u8::from(item)]);
+update_iter_impl!(I64ArrayEntry, i64, 8, le::encode_i64);
+update_iter_impl!(F32ArrayEntry, f32, 4, le::encode_f32);
+update_iter_impl!(F64ArrayEntry, f64, 8, le::encode_f64);
+
 make_entry_type!(StringArrayEntry);
 impl<T: TimeProvider + Clone + Send + Sync> Entry<&[&str]> for StringArrayEntry<T> {
     update_fn!(&[&str]);
 
-    fn update_with_timestamp(&self, data: &[&str], timestamp: u64) -> Result<()> {
-        let length = 4 + 4 * data.len() + data.iter().map(|string| str::len(string)).sum::<usize>();
+    fn update_with_timestamp(&self, data: &[&str], timestamp: u64) -> Result<usize> {
+        self.0
+            .log_data_with_timestamp(encode_string_array(data)?, timestamp)
+    }
+}
+
+// NON-STANDARD:
 
-        let mut dest = vec![0; length].into_boxed_slice();
+/// The type string used by [`PackedBooleanArrayEntry`].
+///
+/// This is NOT a standard type, other tools (like `AdvantageScope`) will only see it as raw bytes.
+pub const PACKED_BOOLEAN_ARRAY_TYPE: &str = "boolean[]packed";
 
+make_entry_type!(PackedBooleanArrayEntry);
+
+/// Logs boolean arrays packed 8 per byte instead of the standard one byte per boolean.
+///
+/// The payload is the number of booleans as a little endian `u32`, followed by the bits with
+/// the first boolean in the least significant bit of the first byte. Use
+/// [`decode_packed_bool_array`] to read it back.
+impl<T: TimeProvider + Clone + Send + Sync> Entry<&[bool]> for PackedBooleanArrayEntry<T> {
+    update_fn!(&[bool]);
+
+    fn update_with_timestamp(&self, data: &[bool], timestamp: u64) -> Result<usize> {
         let data_len: u32 = if let Ok(data_len) = data.len().try_into() {
             data_len
         } else {
-            return Err(format_err!("Data can have at max {} items", u32::MAX));
+            return Err(WpilogError::InvalidInput(format!(
+                "Data can have at max {} items",
+                u32::MAX
+            )));
         };
 
-        let size_encoded = data_len.to_le_bytes();
-        dest[0] = size_encoded[0];
-        dest[1] = size_encoded[1];
-        dest[2] = size_encoded[2];
-        dest[3] = size_encoded[3];
-
-        let mut i = 4;
-        for item in data {
-            let size_encoded = (item.len() as u32).to_le_bytes();
-            dest[i] = size_encoded[0];
-            i += 1;
-            dest[i] = size_encoded[1];
-            i += 1;
-            dest[i] = size_encoded[2];
-            i += 1;
-            dest[i] = size_encoded[3];
-            i += 1;
-
-            let encoded = item.as_bytes();
-            for byte in encoded {
-                dest[i] = *byte;
-                i += 1;
-            }
+        let mut dest = vec![0; 4 + data.len().div_ceil(8)].into_boxed_slice();
+        dest[0..4].copy_from_slice(&le::encode_u32(data_len));
+
+        for (i, item) in data.iter().enumerate() {
+            dest[4 + i / 8] |= u8::from(*item) << (i % 8);
         }
 
         self.0.log_data_with_timestamp(dest, timestamp)
     }
 }
+
+/// Decodes the payload of a [`PackedBooleanArrayEntry`] record.
+pub fn decode_packed_bool_array(data: &[u8]) -> Result<Vec<bool>> {
+    let Some(length) = le::read_u32(data, 0) else {
+        return Err(WpilogError::Truncated("length of packed booleans".into()));
+    };
+    let length = length as usize;
+    let bits = &data[4..];
+
+    if bits.len() != length.div_ceil(8) {
+        return Err(WpilogError::InvalidRecord(format!(
+            "Expected {} bytes of packed booleans, got {}",
+            length.div_ceil(8),
+            bits.len()
+        )));
+    }
+
+    Ok((0..length)
+        .map(|i| bits[i / 8] & (1 << (i % 8)) != 0)
+        .collect())
+}
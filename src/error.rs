@@ -0,0 +1,80 @@
+//! The error type returned by everything in this crate.
+
+use std::{fmt::Display, str::Utf8Error};
+
+use crate::writer::WriterError;
+
+pub type Result<T, E = WpilogError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong reading or writing a log.
+///
+/// This implements [`std::error::Error`], so `?` converts it to an [`anyhow::Error`] (or any other
+/// boxed error) for callers that don't care about matching on it.
+///
+/// [`anyhow::Error`]: https://docs.rs/anyhow/latest/anyhow/struct.Error.html
+#[derive(Debug)]
+pub enum WpilogError {
+    /// The data doesn't start with `WPILOG`
+    InvalidHeader,
+    /// The log is a format version this crate can't read
+    InvalidVersion(u16),
+    /// The data ended partway through something, this says what was being read
+    Truncated(String),
+    /// A record or payload is the wrong shape, like a bad bitfield or a payload of the wrong length
+    InvalidRecord(String),
+    /// A string in the log isn't valid UTF-8
+    Utf8(Utf8Error),
+    /// Something was asked of the writer or reader that it can't do, like logging a non-finite value
+    /// to a [`FiniteEntry`](crate::entrytypes::FiniteEntry)
+    InvalidInput(String),
+    /// Something the log was expected to have, like a checksum trailer, isn't there
+    Missing(String),
+    Io(std::io::Error),
+    /// Failures specific to the writer's worker, like it being closed
+    Writer(WriterError),
+}
+
+impl Display for WpilogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WpilogError::InvalidHeader => write!(f, "Invalid Header"),
+            WpilogError::InvalidVersion(version) => write!(f, "Invalid Version {version:#06x}"),
+            WpilogError::Truncated(what) => write!(f, "Not enough data for {what}"),
+            WpilogError::InvalidRecord(msg)
+            | WpilogError::InvalidInput(msg)
+            | WpilogError::Missing(msg) => write!(f, "{msg}"),
+            WpilogError::Utf8(err) => write!(f, "Invalid UTF-8: {err}"),
+            WpilogError::Io(err) => write!(f, "{err}"),
+            WpilogError::Writer(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for WpilogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WpilogError::Utf8(err) => Some(err),
+            WpilogError::Io(err) => Some(err),
+            WpilogError::Writer(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WpilogError {
+    fn from(err: std::io::Error) -> Self {
+        WpilogError::Io(err)
+    }
+}
+
+impl From<Utf8Error> for WpilogError {
+    fn from(err: Utf8Error) -> Self {
+        WpilogError::Utf8(err)
+    }
+}
+
+impl From<WriterError> for WpilogError {
+    fn from(err: WriterError) -> Self {
+        WpilogError::Writer(err)
+    }
+}
@@ -0,0 +1,340 @@
+//! Exports of decoded logs, as text with one CSV row or JSON Lines object per data record, or (with
+//! the `arrow` feature) as an Arrow table.
+//!
+//! They all take the records from [`WPILOGReader::typed()`](crate::reader::WPILOGReader::typed), so
+//! entries are labelled by name. `raw` and `msgpack` payloads are written with [`to_hex()`], and
+//! `json` entries as a string of their text.
+
+use std::{fmt::Write as _, io::Write};
+
+#[cfg(feature = "arrow")]
+use crate::error::WpilogError;
+use crate::{error::Result, schema::TypedRecord, value::to_hex, value::DecodedValue};
+
+/// How floating point values are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest text that parses back to the exact same value, like `0.30000000000000004`
+    #[default]
+    Lossless,
+    /// Rounded to this many significant digits, then written as short as possible (`0.3`)
+    Significant(usize),
+    /// Always this many digits after the decimal point (`0.300`)
+    Fixed(usize),
+}
+
+impl FloatFormat {
+    #[must_use]
+    pub fn format_f64(self, value: f64) -> String {
+        if !value.is_finite() {
+            return format!("{value}");
+        }
+
+        match self {
+            FloatFormat::Lossless => format!("{value:?}"),
+            FloatFormat::Significant(digits) => {
+                // Rounding through scientific notation keeps the digit count right for any magnitude
+                let rounded: f64 = format!("{value:.*e}", digits.max(1) - 1)
+                    .parse()
+                    .unwrap_or(value);
+                format!("{rounded}")
+            }
+            FloatFormat::Fixed(decimals) => format!("{value:.decimals$}"),
+        }
+    }
+
+    #[must_use]
+    pub fn format_f32(self, value: f32) -> String {
+        match self {
+            // Widening first would print the f32's rounding error as if it were data
+            FloatFormat::Lossless if value.is_finite() => format!("{value:?}"),
+            _ => self.format_f64(f64::from(value)),
+        }
+    }
+}
+
+/// Writes `timestamp,name,value` rows, with a header. Arrays are written as JSON arrays in a quoted
+/// cell.
+pub fn write_csv<W: Write>(
+    records: impl Iterator<Item = Result<TypedRecord>>,
+    mut out: W,
+    floats: FloatFormat,
+) -> Result<W> {
+    writeln!(out, "timestamp,name,value")?;
+
+    for record in records {
+        let record = record?;
+
+        let value = match &record.value {
+            DecodedValue::String(string) | DecodedValue::Json(string) => string.clone(),
+            value if is_array(value) => json_value(value, floats),
+            value => scalar_text(value, floats),
+        };
+
+        writeln!(
+            out,
+            "{},{},{}",
+            record.timestamp,
+            csv_cell(&record.name),
+            csv_cell(&value)
+        )?;
+    }
+
+    Ok(out)
+}
+
+/// Writes one `{"timestamp":..,"name":..,"value":..}` object per line. Non-finite floats are
+/// written as `null` since JSON can't represent them.
+pub fn write_jsonl<W: Write>(
+    records: impl Iterator<Item = Result<TypedRecord>>,
+    mut out: W,
+    floats: FloatFormat,
+) -> Result<W> {
+    for record in records {
+        let record = record?;
+
+        writeln!(
+            out,
+            "{{\"timestamp\":{},\"name\":{},\"value\":{}}}",
+            record.timestamp,
+            json_string(&record.name),
+            json_value(&record.value, floats)
+        )?;
+    }
+
+    Ok(out)
+}
+
+fn is_array(value: &DecodedValue) -> bool {
+    matches!(
+        value,
+        DecodedValue::BooleanArray(_)
+            | DecodedValue::Int64Array(_)
+            | DecodedValue::FloatArray(_)
+            | DecodedValue::DoubleArray(_)
+            | DecodedValue::StringArray(_)
+    )
+}
+
+/// Text for everything but strings and arrays, which need quoting that depends on the format.
+fn scalar_text(value: &DecodedValue, floats: FloatFormat) -> String {
+    match value {
+        DecodedValue::Raw(data) | DecodedValue::Msgpack(data) => to_hex(data),
+        DecodedValue::Boolean(value) => value.to_string(),
+        DecodedValue::Int64(value) => value.to_string(),
+        DecodedValue::Float(value) => floats.format_f32(*value),
+        DecodedValue::Double(value) => floats.format_f64(*value),
+        DecodedValue::Empty => String::new(),
+        _ => unreachable!("Strings and arrays are formatted by the caller"),
+    }
+}
+
+fn json_float(text: String) -> String {
+    // inf and NaN aren't JSON
+    if text.parse::<f64>().is_ok_and(f64::is_finite) {
+        text
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_value(value: &DecodedValue, floats: FloatFormat) -> String {
+    fn array<V>(items: &[V], format: impl Fn(&V) -> String) -> String {
+        let items: Vec<String> = items.iter().map(format).collect();
+        format!("[{}]", items.join(","))
+    }
+
+    match value {
+        DecodedValue::Raw(data) | DecodedValue::Msgpack(data) => json_string(&to_hex(data)),
+        // Kept as a string, embedding it would make a line invalid if the logged JSON is
+        DecodedValue::String(string) | DecodedValue::Json(string) => json_string(string),
+        DecodedValue::Float(_) | DecodedValue::Double(_) => json_float(scalar_text(value, floats)),
+        DecodedValue::BooleanArray(items) => array(items, bool::to_string),
+        DecodedValue::Int64Array(items) => array(items, i64::to_string),
+        DecodedValue::FloatArray(items) => {
+            array(items, |item| json_float(floats.format_f32(*item)))
+        }
+        DecodedValue::DoubleArray(items) => {
+            array(items, |item| json_float(floats.format_f64(*item)))
+        }
+        DecodedValue::StringArray(items) => array(items, |item| json_string(item)),
+        DecodedValue::Empty => "null".to_string(),
+        _ => scalar_text(value, floats),
+    }
+}
+
+pub(crate) fn json_string(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+    escaped.push('"');
+
+    for char in string.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", u32::from(char));
+            }
+            char => escaped.push(char),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// One column of [`to_arrow()`], typed by the first value logged to it.
+#[cfg(feature = "arrow")]
+enum ArrowColumn {
+    Boolean(Vec<Option<bool>>),
+    Int64(Vec<Option<i64>>),
+    Float(Vec<Option<f32>>),
+    Double(Vec<Option<f64>>),
+}
+
+#[cfg(feature = "arrow")]
+impl ArrowColumn {
+    /// A column for this kind of value with `rows` empty rows, or `None` if it's not a scalar.
+    fn new(value: &DecodedValue, rows: usize) -> Option<ArrowColumn> {
+        Some(match value {
+            DecodedValue::Boolean(_) => ArrowColumn::Boolean(vec![None; rows]),
+            DecodedValue::Int64(_) => ArrowColumn::Int64(vec![None; rows]),
+            DecodedValue::Float(_) => ArrowColumn::Float(vec![None; rows]),
+            DecodedValue::Double(_) => ArrowColumn::Double(vec![None; rows]),
+            _ => return None,
+        })
+    }
+
+    /// Starts a new row with the same value as the last one.
+    fn fill_forward(&mut self) {
+        fn repeat<V: Copy>(values: &mut Vec<Option<V>>) {
+            values.push(values.last().copied().flatten());
+        }
+
+        match self {
+            ArrowColumn::Boolean(values) => repeat(values),
+            ArrowColumn::Int64(values) => repeat(values),
+            ArrowColumn::Float(values) => repeat(values),
+            ArrowColumn::Double(values) => repeat(values),
+        }
+    }
+
+    /// Sets the last row's value, ignoring values of a different type than the column.
+    fn set(&mut self, value: &DecodedValue) {
+        fn last<V>(values: &mut [Option<V>], value: Option<V>) {
+            if let Some(slot) = values.last_mut() {
+                *slot = value;
+            }
+        }
+
+        match (self, value) {
+            (ArrowColumn::Boolean(values), DecodedValue::Boolean(value)) => {
+                last(values, Some(*value));
+            }
+            (ArrowColumn::Int64(values), DecodedValue::Int64(value)) => last(values, Some(*value)),
+            (ArrowColumn::Float(values), DecodedValue::Float(value)) => last(values, Some(*value)),
+            (ArrowColumn::Double(values), DecodedValue::Double(value)) => {
+                last(values, Some(*value));
+            }
+            (ArrowColumn::Boolean(values), DecodedValue::Empty) => last(values, None),
+            (ArrowColumn::Int64(values), DecodedValue::Empty) => last(values, None),
+            (ArrowColumn::Float(values), DecodedValue::Empty) => last(values, None),
+            (ArrowColumn::Double(values), DecodedValue::Empty) => last(values, None),
+            _ => {}
+        }
+    }
+
+    fn into_field(self, name: &str) -> (arrow_schema::Field, arrow_array::ArrayRef) {
+        use arrow_array::{BooleanArray, Float32Array, Float64Array, Int64Array};
+        use arrow_schema::{DataType, Field};
+        use std::sync::Arc;
+
+        let (r#type, array): (_, arrow_array::ArrayRef) = match self {
+            ArrowColumn::Boolean(values) => {
+                (DataType::Boolean, Arc::new(BooleanArray::from(values)))
+            }
+            ArrowColumn::Int64(values) => (DataType::Int64, Arc::new(Int64Array::from(values))),
+            ArrowColumn::Float(values) => (DataType::Float32, Arc::new(Float32Array::from(values))),
+            ArrowColumn::Double(values) => {
+                (DataType::Float64, Arc::new(Float64Array::from(values)))
+            }
+        };
+
+        (Field::new(name, r#type, true), array)
+    }
+}
+
+/// Builds an Arrow table with a `timestamp` column and one column per entry, named after it.
+///
+/// There's a row for every timestamp something was logged at, and entries that weren't logged at
+/// that exact time keep their previous value (forward filling), so every row is the full state of
+/// the robot at that time. Before an entry's first value, and after an empty record (see
+/// [`DecodedValue::Empty`]), it's null.
+///
+/// Only `boolean`, `int64`, `float` and `double` entries are included for now, strings, arrays and
+/// everything else are left out. A column's type is that of the first value logged to the name, if
+/// it's restarted with a different type the new values are left out too.
+///
+/// # Errors
+/// If reading a record fails.
+#[cfg(feature = "arrow")]
+pub fn to_arrow(
+    records: impl Iterator<Item = Result<TypedRecord>>,
+) -> Result<arrow_array::RecordBatch> {
+    use arrow_array::{ArrayRef, RecordBatch, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::{collections::HashMap, sync::Arc};
+
+    let mut timestamps: Vec<u64> = vec![];
+    let mut names: Vec<Arc<str>> = vec![];
+    let mut columns: Vec<ArrowColumn> = vec![];
+    let mut by_name: HashMap<Arc<str>, usize> = HashMap::new();
+
+    for record in records {
+        let record = record?;
+
+        let column = if let Some(&column) = by_name.get(&record.name) {
+            column
+        } else {
+            let Some(column) = ArrowColumn::new(&record.value, timestamps.len()) else {
+                continue;
+            };
+
+            columns.push(column);
+            names.push(Arc::clone(&record.name));
+            by_name.insert(record.name, columns.len() - 1);
+            columns.len() - 1
+        };
+
+        if timestamps.last() != Some(&record.timestamp) {
+            timestamps.push(record.timestamp);
+            for column in &mut columns {
+                column.fill_forward();
+            }
+        }
+
+        columns[column].set(&record.value);
+    }
+
+    let mut fields = vec![Field::new("timestamp", DataType::UInt64, false)];
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from(timestamps))];
+    for (name, column) in names.iter().zip(columns) {
+        let (field, array) = column.into_field(name);
+        fields.push(field);
+        arrays.push(array);
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(|err| WpilogError::InvalidInput(format!("Couldn't build the Arrow table: {err}")))
+}
+
+/// Quotes a cell if it has anything CSV treats specially.
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
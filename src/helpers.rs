@@ -0,0 +1,251 @@
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, PoisonError,
+    },
+};
+
+use crate::{
+    entrytypes::{Entry, F64Entry, I64Entry, LogWriter},
+    error::{Result, WpilogError},
+    writer::{TimeProvider, WPILOGWriterBuilder},
+};
+
+/// Tracks the period of a loop and logs its min/mean/max (in seconds) every [`Stopwatch::tick()`].
+pub struct Stopwatch<T: TimeProvider + Clone + Send + Sync> {
+    time_provider: T,
+    min: F64Entry<T>,
+    mean: F64Entry<T>,
+    max: F64Entry<T>,
+    last_tick: Option<u64>,
+    min_period: u64,
+    max_period: u64,
+    total_period: u64,
+    periods: u64,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> Stopwatch<T> {
+    /// Creates the `{name}/min`, `{name}/mean` and `{name}/max` entries.
+    pub fn new(writer: &impl LogWriter<T>, name: &str) -> Result<Self> {
+        Ok(Stopwatch {
+            time_provider: writer.time_provider().clone(),
+            min: writer.new_f64_entry(format!("{name}/min"), None)?,
+            mean: writer.new_f64_entry(format!("{name}/mean"), None)?,
+            max: writer.new_f64_entry(format!("{name}/max"), None)?,
+            last_tick: None,
+            min_period: u64::MAX,
+            max_period: 0,
+            total_period: 0,
+            periods: 0,
+        })
+    }
+
+    /// Call once per loop, the first call only starts timing so nothing is logged until the second.
+    pub fn tick(&mut self) -> Result<()> {
+        let now = self.time_provider.get_time();
+
+        let Some(last_tick) = self.last_tick.replace(now) else {
+            return Ok(());
+        };
+
+        let period = now.saturating_sub(last_tick);
+        self.min_period = self.min_period.min(period);
+        self.max_period = self.max_period.max(period);
+        self.total_period += period;
+        self.periods += 1;
+
+        // Periods are in microseconds, so they won't get anywhere near losing precision
+        #[allow(clippy::cast_precision_loss)]
+        {
+            self.min
+                .update_with_timestamp(self.min_period as f64 / 1_000_000.0, now)?;
+            self.mean.update_with_timestamp(
+                self.total_period as f64 / self.periods as f64 / 1_000_000.0,
+                now,
+            )?;
+            self.max
+                .update_with_timestamp(self.max_period as f64 / 1_000_000.0, now)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An `int64` entry that logs 0, 1, 2, ... on every [`SequenceEntry::tick()`].
+///
+/// Gaps in the logged sequence mean records were dropped somewhere between here and the log file.
+pub struct SequenceEntry<T: TimeProvider + Clone + Send + Sync> {
+    entry: I64Entry<T>,
+    next: AtomicI64,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> SequenceEntry<T> {
+    pub fn new(writer: &impl LogWriter<T>, name: String) -> Result<Self> {
+        Ok(SequenceEntry {
+            entry: writer.new_i64_entry(name, None)?,
+            next: AtomicI64::new(0),
+        })
+    }
+
+    /// Logs the next number in the sequence, returning it.
+    pub fn tick(&self) -> Result<i64> {
+        let sequence = self.next.fetch_add(1, Ordering::Relaxed);
+        self.entry.update(sequence)?;
+
+        Ok(sequence)
+    }
+}
+
+/// Logs values along with when they were captured, for measuring latency.
+///
+/// WPILOG only has one timestamp per record, so every update is logged at the time it's published
+/// and the capture time goes to a companion `int64` entry, logged with the same timestamp so the two
+/// can be matched up.
+pub struct TimestampedEntry<T: TimeProvider + Clone + Send + Sync, E> {
+    entry: E,
+    capture_time: I64Entry<T>,
+    time_provider: T,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, E> TimestampedEntry<T, E> {
+    /// Wraps `entry`, creating the `capture_name` entry for the capture times.
+    pub fn new(writer: &impl LogWriter<T>, entry: E, capture_name: String) -> Result<Self> {
+        Ok(TimestampedEntry {
+            entry,
+            capture_time: writer.new_i64_entry(capture_name, None)?,
+            time_provider: writer.time_provider().clone(),
+        })
+    }
+
+    /// Logs `value` now, and that it was captured at `capture_time`.
+    pub fn update<V>(&self, value: V, capture_time: u64) -> Result<()>
+    where
+        E: Entry<V>,
+    {
+        let Ok(capture_time) = i64::try_from(capture_time) else {
+            return Err(WpilogError::InvalidInput(format!(
+                "Capture time {capture_time} doesn't fit in an int64"
+            )));
+        };
+
+        let now = self.time_provider.get_time();
+        self.entry.update_with_timestamp(value, now)?;
+        self.capture_time.update_with_timestamp(capture_time, now)?;
+
+        Ok(())
+    }
+}
+
+/// An entry that isn't created (so no `Start` record is logged) until its first update.
+///
+/// For signals that might never fire, so they don't leave an empty `Start`/`Finish` pair in the
+/// log. `make` is usually a closure calling one of the [`LogWriter`] constructors, like
+/// `LazyEntry::new(|| writer.new_f64_entry("arm/error".into(), None))`. It runs exactly once, even
+/// if several threads update at the same time, unless it fails, in which case the next update
+/// tries again.
+pub struct LazyEntry<E, F: Fn() -> Result<E>> {
+    make: F,
+    entry: OnceLock<E>,
+    creating: Mutex<()>,
+}
+
+impl<E, F: Fn() -> Result<E>> LazyEntry<E, F> {
+    pub fn new(make: F) -> Self {
+        LazyEntry {
+            make,
+            entry: OnceLock::new(),
+            creating: Mutex::new(()),
+        }
+    }
+
+    /// The entry, if it has been created yet.
+    pub fn get(&self) -> Option<&E> {
+        self.entry.get()
+    }
+
+    /// The entry, creating it if this is the first use.
+    pub fn get_or_create(&self) -> Result<&E> {
+        if let Some(entry) = self.entry.get() {
+            return Ok(entry);
+        }
+
+        let _creating = self.creating.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Someone else might have created it while we were waiting
+        if let Some(entry) = self.entry.get() {
+            return Ok(entry);
+        }
+
+        let entry = (self.make)()?;
+        Ok(self.entry.get_or_init(|| entry))
+    }
+}
+
+impl<V, E: Entry<V>, F: Fn() -> Result<E>> Entry<V> for LazyEntry<E, F> {
+    fn update(&self, data: V) -> Result<usize> {
+        self.get_or_create()?.update(data)
+    }
+
+    fn update_with_timestamp(&self, data: V, timestamp: u64) -> Result<usize> {
+        self.get_or_create()?.update_with_timestamp(data, timestamp)
+    }
+}
+
+/// One entry of a log made by [`build_log()`].
+#[derive(Debug, Clone)]
+pub struct EntrySpec {
+    pub name: String,
+    pub type_str: String,
+    /// `(timestamp, payload)` pairs, the payloads have to already be encoded for `type_str`
+    pub samples: Vec<(u64, Box<[u8]>)>,
+}
+
+/// Time for [`build_log()`], set by hand so control records line up with the samples.
+#[derive(Clone)]
+struct FixtureClock(Arc<AtomicU64>);
+
+impl TimeProvider for FixtureClock {
+    fn get_time(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Writes a complete log to `out`, mostly useful for test fixtures.
+///
+/// Every entry is started at the earliest sample's timestamp and finished at the latest one. The
+/// samples from all entries are logged in timestamp order, samples with the same timestamp stay in
+/// the order they were given in.
+///
+/// # Errors
+/// If writing to `out` fails, including the header.
+pub fn build_log<W: Write + Send + 'static>(out: W, entries: &[EntrySpec]) -> Result<W> {
+    let mut samples: Vec<(u64, usize, &[u8])> = entries
+        .iter()
+        .enumerate()
+        .flat_map(|(i, spec)| {
+            spec.samples
+                .iter()
+                .map(move |(timestamp, data)| (*timestamp, i, &**data))
+        })
+        .collect();
+    samples.sort_by_key(|(timestamp, _, _)| *timestamp);
+
+    let clock = FixtureClock(Arc::new(AtomicU64::new(
+        samples.first().map_or(0, |(timestamp, _, _)| *timestamp),
+    )));
+    let writer = WPILOGWriterBuilder::new().build(out, clock.clone())?;
+
+    let raw_entries = entries
+        .iter()
+        .map(|spec| writer.make_entry(spec.name.clone(), spec.type_str.clone(), String::new()))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (timestamp, i, data) in samples {
+        clock.0.store(timestamp, Ordering::Relaxed);
+        raw_entries[i].log_slice_with_timestamp(data, timestamp)?;
+    }
+
+    drop(raw_entries);
+    writer.join()
+}
@@ -0,0 +1,57 @@
+//! Little endian encoding and decoding for every numeric width WPILOG uses.
+//!
+//! WPILOG is little endian no matter what the target is, so log data should always go through
+//! here instead of using `to_ne_bytes`/`from_ne_bytes` (or `to_be_bytes`) by accident.
+
+macro_rules! le_impl {
+    ($encode:ident, $decode:ident, $type:ty, $size:literal) => {
+        pub(crate) fn $encode(value: $type) -> [u8; $size] {
+            value.to_le_bytes()
+        }
+
+        pub(crate) fn $decode(bytes: [u8; $size]) -> $type {
+            <$type>::from_le_bytes(bytes)
+        }
+    };
+}
+
+le_impl!(encode_u16, decode_u16, u16, 2);
+le_impl!(encode_u32, decode_u32, u32, 4);
+le_impl!(encode_u64, decode_u64, u64, 8);
+le_impl!(encode_i64, decode_i64, i64, 8);
+le_impl!(encode_f32, decode_f32, f32, 4);
+le_impl!(encode_f64, decode_f64, f64, 8);
+
+/// Only ever decoded, for reading `int64[]` payloads from before elements were 8 bytes wide.
+pub(crate) fn decode_i32(bytes: [u8; 4]) -> i32 {
+    i32::from_le_bytes(bytes)
+}
+
+/// Reads the `u32` at `data[ptr..ptr + 4]`, or [`None`] if there aren't enough bytes.
+pub(crate) fn read_u32(data: &[u8], ptr: usize) -> Option<u32> {
+    let bytes = data.get(ptr..ptr.checked_add(4)?)?;
+
+    Some(decode_u32([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Encodes every item in `data` back to back.
+pub(crate) fn encode_slice<V: Copy, const N: usize>(
+    data: &[V],
+    encode: fn(V) -> [u8; N],
+) -> Box<[u8]> {
+    encode_iter(data.iter().copied(), encode).into_boxed_slice()
+}
+
+/// Encodes every item of `data` back to back, the exact length means it's only allocated once.
+pub(crate) fn encode_iter<V, const N: usize>(
+    data: impl ExactSizeIterator<Item = V>,
+    encode: fn(V) -> [u8; N],
+) -> Vec<u8> {
+    let mut dest = Vec::with_capacity(data.len() * N);
+
+    for item in data {
+        dest.extend_from_slice(&encode(item));
+    }
+
+    dest
+}
@@ -10,11 +10,57 @@
 
 static HEADER_STRING: &[u8; 6] = b"WPILOG";
 static HEADER_VERSION: u16 = 0x0100;
+/// Starts the extra header line added by `WPILOGWriterBuilder::wall_clock_start()`
+static WALL_CLOCK_PREFIX: &str = "wall_clock_start=";
 
+pub mod checksum;
+pub mod diff;
 pub mod entrytypes;
+pub mod error;
+pub mod export;
+pub mod helpers;
+mod le;
+pub mod net;
 pub mod reader;
+pub mod schema;
+pub mod slice;
+pub mod types;
+pub mod validate;
+pub mod value;
 pub mod writer;
 
+/// The magic and version at the start of a log. [`Default`] is the standard `WPILOG` version 1.0,
+/// anything else is only for vendor variants or testing how readers handle a mismatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderFormat {
+    pub magic: [u8; 6],
+    pub version: u16,
+}
+
+impl Default for HeaderFormat {
+    fn default() -> Self {
+        HeaderFormat {
+            magic: *HEADER_STRING,
+            version: HEADER_VERSION,
+        }
+    }
+}
+
+impl HeaderFormat {
+    /// Checks the magic and version a log starts with against this format.
+    pub(crate) fn check(self, magic: [u8; 6], version: u16) -> error::Result<()> {
+        if magic != self.magic {
+            return Err(error::WpilogError::InvalidHeader);
+        }
+
+        if version != self.version {
+            return Err(error::WpilogError::InvalidVersion(version));
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Record {
     pub id: u32,
@@ -38,3 +84,44 @@ pub enum ControlData {
     Finish,
     SetMetadata(Box<str>),
 }
+
+/// Which kind of control record something is, without any of its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControlKind {
+    Start,
+    Finish,
+    SetMetadata,
+}
+
+impl ControlData {
+    #[must_use]
+    pub fn kind(&self) -> ControlKind {
+        match self {
+            ControlData::Start { .. } => ControlKind::Start,
+            ControlData::Finish => ControlKind::Finish,
+            ControlData::SetMetadata(_) => ControlKind::SetMetadata,
+        }
+    }
+
+    /// Reads just the kind and entry id from a control record's payload, skipping (and not checking)
+    /// the strings after them.
+    pub fn peek_kind(data: &[u8]) -> error::Result<(ControlKind, u32)> {
+        let kind = match data.first() {
+            Some(0) => ControlKind::Start,
+            Some(1) => ControlKind::Finish,
+            Some(2) => ControlKind::SetMetadata,
+            Some(kind) => {
+                return Err(error::WpilogError::InvalidRecord(format!(
+                    "Invalid Control Record Type: {kind}"
+                )))
+            }
+            None => return Err(error::WpilogError::Truncated("control record type".into())),
+        };
+
+        let Some(id) = le::read_u32(data, 1) else {
+            return Err(error::WpilogError::Truncated("entry id".into()));
+        };
+
+        Ok((kind, id))
+    }
+}
@@ -0,0 +1,296 @@
+//! Streaming a log to a server over TCP, see [`NetworkSink`].
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
+
+use crate::{error::WpilogError, le, slice::parse_record_ref};
+
+/// How long connecting or writing can block the writer's thread before the connection is given up on.
+const IO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The most bytes to wait for a record to be completed before giving up on it, the same as the
+/// default [`ReaderOptions::max_record_size`](crate::reader::ReaderOptions::max_record_size) since
+/// the server likely couldn't read a bigger one anyway.
+const MAX_PENDING: usize = 64 * 1024 * 1024;
+
+/// A complete record, as it was written.
+struct Framed {
+    bytes: Box<[u8]>,
+    /// The control record kind and the entry it's for
+    control: Option<(u8, u32)>,
+}
+
+/// What the server needs to be told again after reconnecting for an entry to still make sense.
+struct OpenEntry {
+    start: Box<[u8]>,
+    /// The latest `SetMetadata` record, if there's been one
+    metadata: Option<Box<[u8]>>,
+}
+
+/// A [`Write`] for [`WPILOGWriter`](crate::writer::WPILOGWriter) that streams the log to a TCP
+/// server, surviving disconnects.
+///
+/// The log is split back into records, and only whole records are sent. When the connection drops,
+/// records are kept in a backlog (up to [`NetworkSink::max_backlog()`] bytes, the oldest are dropped
+/// after that) while it tries to reconnect every [`NetworkSink::retry_interval()`]. Every new
+/// connection starts like a fresh log: the header, then the `Start` (and latest `SetMetadata`)
+/// records of every entry still open, then the backlog. So the server should treat each connection
+/// as its own log.
+///
+/// Network errors are never returned, logging carries on while disconnected. TCP only notices a
+/// dead connection a few writes late, so records written just before a disconnect can be lost. A checksum trailer
+/// won't match anything the server received if there were reconnects.
+pub struct NetworkSink {
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+    last_attempt: Option<Instant>,
+    retry_interval: Duration,
+    /// Written bytes that aren't a whole record (or header) yet
+    pending: Vec<u8>,
+    header: Option<Box<[u8]>>,
+    /// Entries open as of the last record sent (or dropped)
+    open: BTreeMap<u32, OpenEntry>,
+    backlog: VecDeque<Framed>,
+    backlog_bytes: usize,
+    max_backlog: usize,
+    dropped: u64,
+    connections: u64,
+}
+
+impl NetworkSink {
+    /// Streams to `addr`. Nothing is connected until the log's header is written.
+    #[must_use]
+    pub fn new(addr: SocketAddr) -> NetworkSink {
+        NetworkSink {
+            addr,
+            stream: None,
+            last_attempt: None,
+            retry_interval: Duration::from_secs(1),
+            pending: vec![],
+            header: None,
+            open: BTreeMap::new(),
+            backlog: VecDeque::new(),
+            backlog_bytes: 0,
+            max_backlog: 1024 * 1024,
+            dropped: 0,
+            connections: 0,
+        }
+    }
+
+    /// How long to wait between connection attempts. Defaults to 1 second.
+    #[must_use]
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// How many bytes of records to keep while disconnected. Defaults to 1 MiB.
+    #[must_use]
+    pub fn max_backlog(mut self, max_backlog: usize) -> Self {
+        self.max_backlog = max_backlog;
+        self
+    }
+
+    /// If the last write went through.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// How many records didn't fit in the backlog and were never sent. Bytes that can't be split
+    /// into records at all are dropped too, and count as one.
+    #[must_use]
+    pub fn dropped_records(&self) -> u64 {
+        self.dropped
+    }
+
+    /// How many times a connection was made, so anything over 1 means there were reconnects.
+    #[must_use]
+    pub fn connections(&self) -> u64 {
+        self.connections
+    }
+
+    /// Splits whole records (and the header) out of `pending` and sends them.
+    fn process_pending(&mut self) {
+        let mut ptr = 0;
+
+        if self.header.is_none() {
+            let Some(length) = le::read_u32(&self.pending, 8) else {
+                return;
+            };
+            let Some(header) = self.pending.get(..12 + length as usize) else {
+                return;
+            };
+
+            self.header = Some(header.into());
+            ptr = header.len();
+        }
+
+        loop {
+            let (record, length) = match parse_record_ref(&self.pending[ptr..]) {
+                Ok(parsed) => parsed,
+                // The rest of the record just hasn't been written yet, unless it never will be
+                Err(WpilogError::Truncated(_)) if self.pending.len() - ptr <= MAX_PENDING => break,
+                Err(_) => {
+                    // There's no telling where the next record starts, so all of it is lost
+                    self.pending.clear();
+                    self.dropped += 1;
+                    return;
+                }
+            };
+
+            let control = if record.id == 0 {
+                record
+                    .data
+                    .first()
+                    .zip(le::read_u32(record.data, 1))
+                    .map(|(&kind, id)| (kind, id))
+            } else {
+                None
+            };
+
+            let framed = Framed {
+                bytes: self.pending[ptr..ptr + length].into(),
+                control,
+            };
+            ptr += length;
+
+            self.send(framed);
+        }
+
+        self.pending.drain(..ptr);
+    }
+
+    fn send(&mut self, record: Framed) {
+        self.reconnect_if_due();
+
+        if self.backlog.is_empty() {
+            if let Some(stream) = &mut self.stream {
+                if stream.write_all(&record.bytes).is_ok() {
+                    self.track(record);
+                    return;
+                }
+
+                self.stream = None;
+            }
+        }
+
+        self.backlog_bytes += record.bytes.len();
+        self.backlog.push_back(record);
+
+        while self.backlog_bytes > self.max_backlog {
+            let Some(record) = self.backlog.pop_front() else {
+                break;
+            };
+
+            self.backlog_bytes -= record.bytes.len();
+            self.dropped += 1;
+            // The data is gone, but the server still needs to know which entries exist
+            self.track(record);
+        }
+    }
+
+    /// Updates which entries are open with a record that was sent (or dropped).
+    fn track(&mut self, record: Framed) {
+        match record.control {
+            Some((0, id)) => {
+                self.open.insert(
+                    id,
+                    OpenEntry {
+                        start: record.bytes,
+                        metadata: None,
+                    },
+                );
+            }
+            Some((1, id)) => {
+                self.open.remove(&id);
+            }
+            Some((2, id)) => {
+                if let Some(entry) = self.open.get_mut(&id) {
+                    entry.metadata = Some(record.bytes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reconnect_if_due(&mut self) {
+        if self.stream.is_some() {
+            return;
+        }
+        if self
+            .last_attempt
+            .is_some_and(|last| last.elapsed() < self.retry_interval)
+        {
+            return;
+        }
+        let Some(header) = &self.header else {
+            return;
+        };
+
+        self.last_attempt = Some(Instant::now());
+
+        let Ok(mut stream) = TcpStream::connect_timeout(&self.addr, IO_TIMEOUT) else {
+            return;
+        };
+        if stream.set_write_timeout(Some(IO_TIMEOUT)).is_err() {
+            return;
+        }
+        let _ = stream.set_nodelay(true);
+
+        let replayed = stream.write_all(header).and_then(|()| {
+            self.open.values().try_for_each(|entry| {
+                stream.write_all(&entry.start)?;
+                match &entry.metadata {
+                    Some(metadata) => stream.write_all(metadata),
+                    None => Ok(()),
+                }
+            })
+        });
+        if replayed.is_err() {
+            return;
+        }
+
+        self.connections += 1;
+
+        while let Some(record) = self.backlog.pop_front() {
+            self.backlog_bytes -= record.bytes.len();
+
+            if stream.write_all(&record.bytes).is_err() {
+                // Not sent, so it's still the oldest record for the next connection
+                self.backlog_bytes += record.bytes.len();
+                self.backlog.push_front(record);
+                return;
+            }
+
+            self.track(record);
+        }
+
+        self.stream = Some(stream);
+    }
+}
+
+impl Write for NetworkSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        self.process_pending();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.reconnect_if_due();
+
+        if let Some(stream) = &mut self.stream {
+            if stream.flush().is_err() {
+                self.stream = None;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,48 @@
-use anyhow::{format_err, Result};
 use core::str;
-use std::io::{BufReader, Read};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+    thread,
+    time::Duration,
+};
 
-use crate::{ControlData, Record, RecordInfo, HEADER_STRING, HEADER_VERSION};
+use crate::{
+    checksum::{self, Crc32},
+    error::{Result, WpilogError},
+    le,
+    schema::{SchemaReader, TypedRecords},
+    value::{decode_bool, DecodedValue},
+    ControlData, ControlKind, HeaderFormat, Record, RecordInfo, WALL_CLOCK_PREFIX,
+};
 
 pub struct WPILOGReader<R: Read> {
     reader: R,
     pub extra_header: Box<[u8]>,
+    /// Offset of the first record, assuming the log started at offset 0 of the reader
+    records_start: u64,
+    index: Option<Box<[IndexEntry]>>,
+    max_record_size: usize,
+}
+
+/// Settings for [`WPILOGReader::with_options()`], the [`Default`] is what [`WPILOGReader::new_raw()`] uses.
+#[derive(Debug, Clone)]
+pub struct ReaderOptions {
+    /// The header the log has to start with
+    pub header: HeaderFormat,
+    /// Records claiming a bigger payload than this are an error instead of being allocated, so a
+    /// corrupt size can't run the process out of memory. Defaults to 64 MiB.
+    pub max_record_size: usize,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            header: HeaderFormat::default(),
+            max_record_size: 64 * 1024 * 1024,
+        }
+    }
 }
 
 impl<R: Read> WPILOGReader<BufReader<R>> {
@@ -17,69 +53,489 @@ impl<R: Read> WPILOGReader<BufReader<R>> {
     }
 }
 
+/// The reader returned by [`WPILOGReader::open_auto()`], over either the file itself or its
+/// decompressed contents.
+#[cfg(feature = "gzip")]
+pub type AutoReader = WPILOGReader<Box<dyn Read + Send>>;
+
+#[cfg(feature = "gzip")]
+impl WPILOGReader<Box<dyn Read + Send>> {
+    /// Opens a log that may or may not be gzip compressed, checking for the gzip magic bytes at the
+    /// start. Use [`WPILOGReader::new_buffered()`] if it's known to be uncompressed.
+    pub fn open_auto(path: impl AsRef<Path>) -> Result<AutoReader> {
+        use std::io::BufRead;
+
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        let mut file = BufReader::new(File::open(path)?);
+
+        let reader: Box<dyn Read + Send> = if file.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(file)))
+        } else {
+            Box::new(file)
+        };
+
+        WPILOGReader::new_raw(reader)
+    }
+}
+
 impl<R: Read> WPILOGReader<R> {
     /// Using [`WPIReader::new_buffered()`], or passing an already buffered reader is HIGHLY recommended
-    pub fn new_raw(mut reader: R) -> Result<Self> {
-        // Read and check header
+    pub fn new_raw(reader: R) -> Result<Self> {
+        WPILOGReader::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Like [`WPILOGReader::new_raw()`], but with non default [`ReaderOptions`].
+    pub fn with_options(mut reader: R, options: &ReaderOptions) -> Result<Self> {
+        // Read and check header and version number
         let mut header = [0; 6];
         reader.read_exact(&mut header)?;
 
-        if header != *HEADER_STRING {
-            return Err(format_err!("Invalid Header"));
-        }
-
-        // Read and check version number
         let mut version = [0; 2];
         reader.read_exact(&mut version)?;
-        let version = u16::from_le_bytes(version);
-
-        if version != HEADER_VERSION {
-            return Err(format_err!("Invalid Version"));
-        }
+        options.header.check(header, le::decode_u16(version))?;
 
         // Read and save extra header
         let mut length = [0; 4];
         reader.read_exact(&mut length)?;
-        let length = u32::from_le_bytes(length).try_into()?;
+        let length = le::decode_u32(length);
+
+        // Read through `take` so a corrupt length can't make us allocate more than the log has left
+        let mut extra_header = vec![];
+        (&mut reader)
+            .take(u64::from(length))
+            .read_to_end(&mut extra_header)?;
 
-        let mut extra_header = vec![0; length].into_boxed_slice();
-        reader.read_exact(&mut extra_header)?;
+        if extra_header.len() != length as usize {
+            return Err(WpilogError::Truncated(format!(
+                "extra header, its length is {length} bytes but only {} are left",
+                extra_header.len()
+            )));
+        }
+
+        let extra_header = extra_header.into_boxed_slice();
 
         Ok(WPILOGReader {
             reader,
             extra_header,
+            records_start: 12 + u64::from(length),
+            index: None,
+            max_record_size: options.max_record_size,
         })
     }
 
-    /// Preconditions: `length <= 8`
-    fn read_variable_int(&mut self, length: usize) -> Result<u64> {
-        debug_assert!(length <= 8, "Invalid variable int length {length}");
+    /// Turns this into a reader for a log that is still being written to.
+    ///
+    /// Instead of stopping at the end of the file, the returned reader sleeps for `poll_interval`
+    /// and tries again until more data shows up. Since this also happens in the middle of a record,
+    /// a partially written record is finished once the rest of it is written instead of being misparsed.
+    ///
+    /// The iterator will never end on its own, only if the underlying reader errors.
+    pub fn records_tailing(self, poll_interval: Duration) -> WPILOGReader<TailReader<R>> {
+        WPILOGReader {
+            reader: TailReader {
+                inner: self.reader,
+                poll_interval,
+            },
+            extra_header: self.extra_header,
+            records_start: self.records_start,
+            index: self.index,
+            max_record_size: self.max_record_size,
+        }
+    }
 
-        let mut final_buf: Box<[u8; 8]> = Box::from([0; 8]);
-        self.reader.read_exact(&mut final_buf[0..length])?;
+    /// The Unix time (in microseconds) and log timestamp of the same moment, if the log was written with
+    /// [`WPILOGWriterBuilder::wall_clock_start()`].
+    ///
+    /// [`WPILOGWriterBuilder::wall_clock_start()`]: crate::writer::WPILOGWriterBuilder::wall_clock_start
+    #[must_use]
+    pub fn wall_clock_start(&self) -> Option<(u64, u64)> {
+        let header = str::from_utf8(&self.extra_header).ok()?;
+        let line = header
+            .lines()
+            .find_map(|line| line.strip_prefix(WALL_CLOCK_PREFIX))?;
+        let (unix, timestamp) = line.split_once('@')?;
+
+        Some((unix.parse().ok()?, timestamp.parse().ok()?))
+    }
+
+    /// Parses the session metadata written with
+    /// [`WPILOGWriterBuilder::session_metadata()`], the JSON on the first line of the extra header.
+    ///
+    /// # Errors
+    /// [`WpilogError::InvalidRecord`] if the extra header doesn't start with JSON matching `T`.
+    ///
+    /// [`WPILOGWriterBuilder::session_metadata()`]: crate::writer::WPILOGWriterBuilder::session_metadata
+    #[cfg(feature = "serde_json")]
+    pub fn session_metadata<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        let header = str::from_utf8(&self.extra_header)?;
+        let line = header.lines().next().unwrap_or_default();
+
+        serde_json::from_str(line).map_err(|err| {
+            WpilogError::InvalidRecord(format!("Extra header isn't valid session metadata: {err}"))
+        })
+    }
+
+    /// Converts a record timestamp to the wall clock time it was logged at, see
+    /// [`WPILOGReader::wall_clock_start()`].
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn wall_clock(&self, timestamp: u64) -> Option<chrono::DateTime<chrono::Utc>> {
+        let (unix, start) = self.wall_clock_start()?;
+        // The header can be anything, so none of this is trusted not to overflow
+        let elapsed = i64::try_from(timestamp)
+            .ok()?
+            .checked_sub(i64::try_from(start).ok()?)?;
+        let micros = i64::try_from(unix).ok()?.checked_add(elapsed)?;
+
+        chrono::DateTime::from_timestamp_micros(micros)
+    }
+
+    /// The smallest and largest record timestamps from here to the end of the log. Control records
+    /// count too, so from the start this covers everything from the first `Start` to the last `Finish`.
+    ///
+    /// If [`WPILOGReader::build_index()`] has been called, this uses the index and doesn't read
+    /// anything. Otherwise it reads just the record headers, skipping the payloads, and the reader is
+    /// left at the end (a seekable one can be [rewound](WPILOGReader::rewind)). Like the index it
+    /// stops at the first record that can't be read.
+    ///
+    /// # Errors
+    /// If there are no records, or skipping a payload fails.
+    pub fn time_span(&mut self) -> Result<(u64, u64)> {
+        let span = if let Some(index) = &self.index {
+            index
+                .iter()
+                .fold(None, |span, entry| Some(widen(span, entry.timestamp)))
+        } else {
+            let mut span = None;
+
+            while let Some(header) = self.read_record_header() {
+                let skipped = std::io::copy(
+                    &mut (&mut self.reader).take(header.size as u64),
+                    &mut std::io::sink(),
+                )?;
+                if skipped != header.size as u64 {
+                    break;
+                }
+
+                span = Some(widen(span, header.timestamp));
+            }
+
+            span
+        };
+
+        span.ok_or_else(|| WpilogError::Missing("Log has no records".into()))
+    }
+
+    /// Wraps this in a [`SchemaReader`], which keeps track of what entry every id is.
+    pub fn schema(self) -> SchemaReader<Self> {
+        SchemaReader::new(self)
+    }
+
+    /// Yields just the data records, decoded by their entry's type, with all the control record
+    /// bookkeeping done internally. This is the same as `reader.schema().typed()`.
+    pub fn typed(self) -> TypedRecords<Self> {
+        self.schema().typed()
+    }
+
+    /// Reads every remaining record, collecting the decoded values logged to the entry called `name`.
+    ///
+    /// If the entry was started more than once (for example across a rotation), all of them are included.
+    pub fn series_for_name(&mut self, name: &str) -> Result<Vec<(u64, DecodedValue)>> {
+        let mut schema = SchemaReader::new(self.by_ref());
+        let mut series = vec![];
+
+        while let Some(record) = schema.next().transpose()? {
+            let RecordInfo::Data(data) = record.info else {
+                continue;
+            };
 
-        Ok(u64::from_le_bytes(*final_buf))
+            if let Some(entry) = schema.entry(record.id) {
+                if &*entry.name == name {
+                    series.push((
+                        record.timestamp,
+                        DecodedValue::decode(&entry.r#type, &data)?,
+                    ));
+                }
+            }
+        }
+
+        Ok(series)
     }
 
-    /// Preconditions: `length <= 8`
-    fn read_variable_int_option(&mut self, length: usize) -> Option<u64> {
-        match self.read_variable_int(length) {
-            Ok(value) => Some(value),
-            // TODO: actually check what the error is
-            Err(_err) => None,
+    /// Reads every remaining record, returning the value every entry had as of `timestamp`, keyed
+    /// by name.
+    ///
+    /// Entries that haven't logged anything by then are left out. Finished entries keep their last
+    /// value, since most logs only finish entries when they're closed. Records after `timestamp` are
+    /// still read (logs aren't guaranteed to be perfectly sorted), but ignored.
+    pub fn snapshot_at(&mut self, timestamp: u64) -> Result<HashMap<String, DecodedValue>> {
+        let mut schema = SchemaReader::new(self.by_ref());
+        let mut snapshot = HashMap::new();
+
+        while let Some(record) = schema.next().transpose()? {
+            if record.timestamp > timestamp {
+                continue;
+            }
+
+            let RecordInfo::Data(data) = record.info else {
+                continue;
+            };
+
+            if let Some(entry) = schema.entry(record.id) {
+                snapshot.insert(
+                    entry.name.to_string(),
+                    DecodedValue::decode(&entry.r#type, &data)?,
+                );
+            }
         }
+
+        Ok(snapshot)
     }
-}
 
-impl<R: Read> Iterator for WPILOGReader<R> {
-    type Item = PlainRecord;
+    /// Reads every remaining record, tallying how many data records and payload bytes each entry
+    /// name logged, to find which entries take up most of a log.
+    ///
+    /// Entries started more than once under the same name are added together.
+    pub fn size_breakdown(&mut self) -> Result<HashMap<String, EntrySize>> {
+        let mut schema = SchemaReader::new(self.by_ref());
+        let mut sizes: HashMap<String, EntrySize> = HashMap::new();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut bitfield = [0; 1];
+        while let Some(record) = schema.next().transpose()? {
+            let RecordInfo::Data(data) = record.info else {
+                continue;
+            };
+
+            if let Some(entry) = schema.entry(record.id) {
+                let size = sizes.entry(entry.name.to_string()).or_default();
+                size.records += 1;
+                size.bytes += data.len() as u64;
+            }
+        }
+
+        Ok(sizes)
+    }
+
+    /// Reads every remaining record, routing the data records into a separate iterator per entry id
+    /// so each entry can be handed off and processed on its own.
+    ///
+    /// Everything is buffered before this returns, so it holds the whole rest of the log in memory
+    /// at once (plus a small allocation per record). Control records aren't included.
+    pub fn split_by_entry(&mut self) -> Result<HashMap<u32, std::vec::IntoIter<PlainRecord>>> {
+        let mut entries: HashMap<u32, Vec<PlainRecord>> = HashMap::new();
+
+        while let Some(record) = self.read_record()? {
+            if record.is_data() {
+                entries.entry(record.id).or_default().push(record);
+            }
+        }
+
+        Ok(entries
+            .into_iter()
+            .map(|(id, records)| (id, records.into_iter()))
+            .collect())
+    }
+
+    /// Groups consecutive records that share a timestamp into [`Frame`]s.
+    ///
+    /// Only *consecutive* records are grouped, this relies on the writer logging everything for a
+    /// timestamp together. If records with the same timestamp are interleaved with other
+    /// timestamps, each run of them becomes its own frame instead of being merged.
+    pub fn frames(self) -> Frames<R> {
+        Frames {
+            reader: self,
+            pending: None,
+        }
+    }
+
+    /// Joins payloads that were split across several records back into one record, for custom
+    /// entries whose data is too big for a single record.
+    ///
+    /// `is_continuation` says if a record continues the one before it, in which case its payload is
+    /// appended to that record (keeping the first record's timestamp). Only records with the same id
+    /// directly after each other are joined, and control records never are.
+    pub fn reassembled<F: FnMut(&PlainRecord) -> bool>(
+        self,
+        is_continuation: F,
+    ) -> Reassembled<R, F> {
+        Reassembled {
+            reader: self,
+            is_continuation,
+            pending: None,
+        }
+    }
+
+    /// Leaves out data records whose payload is the same as the previous one for their entry, for
+    /// signals logged every loop that rarely change.
+    ///
+    /// Control records are passed through unchanged. A new `Start` for an id forgets its last
+    /// payload, so the first record after a restart is always kept.
+    pub fn changes_only(self) -> ChangesOnly<R> {
+        ChangesOnly {
+            reader: self,
+            last: HashMap::new(),
+        }
+    }
+
+    /// The level changes of the `boolean` entry `id`, as `(timestamp, new level)`, for turning a
+    /// sensor logged every loop into rising and falling edges.
+    ///
+    /// The first value (and the first one after a new `Start` for `id`) is always yielded, since
+    /// there's nothing before it to compare to. Records that aren't a valid boolean are skipped.
+    pub fn boolean_edges(self, id: u32) -> BooleanEdges<R> {
+        BooleanEdges {
+            reader: self,
+            id,
+            last: None,
+        }
+    }
+
+    /// Calls `f` with every remaining record, reading them all into the same buffer.
+    ///
+    /// This avoids allocating for every record like iterating does, but the record can't outlive the call.
+    ///
+    /// # Errors
+    /// Stops at the first record that can't be read, returning why, so a truncated or corrupt log
+    /// isn't mistaken for one that ended cleanly. `f` has been called for every record before it.
+    pub fn for_each_record(&mut self, mut f: impl FnMut(&PlainRecordRef)) -> Result<()> {
+        let mut buf = vec![];
+
+        while let Some(header) = self.try_read_record_header()? {
+            self.read_payload_into(header.size, &mut buf)?;
+
+            f(&PlainRecordRef {
+                id: header.id,
+                timestamp: header.timestamp,
+                data: &buf,
+            });
+        }
+
+        Ok(())
+    }
 
+    /// Reads every remaining record, calling `on_control` with each decoded control record (and the
+    /// entry id and timestamp) or `on_data` with each data record.
+    ///
+    /// Stops at the first record that can't be read or decoded, returning why.
+    pub fn for_each_event(
+        &mut self,
+        mut on_control: impl FnMut(&ControlData, u32, u64),
+        mut on_data: impl FnMut(&PlainRecord),
+    ) -> Result<()> {
+        while let Some(record) = self.read_record()? {
+            if record.is_data() {
+                on_data(&record);
+                continue;
+            }
+
+            let record = Record::try_from(record)?;
+            if let RecordInfo::Control(control) = &record.info {
+                on_control(control, record.id, record.timestamp);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the next record, unlike iterating this reports why reading failed.
+    ///
+    /// Returns `Ok(None)` if the log ended cleanly between two records.
+    pub fn read_record(&mut self) -> Result<Option<PlainRecord>> {
+        Ok(self
+            .read_record_with_encoding()?
+            .map(|(record, _encoding)| record))
+    }
+
+    /// Yields every record along with how its header was encoded, for inspecting what an encoder did.
+    pub fn records_with_encoding(self) -> RecordsWithEncoding<R> {
+        RecordsWithEncoding { reader: self }
+    }
+
+    /// Yields every record's exact bytes from the log, header included, so records that don't need
+    /// changing can be copied to another log as-is.
+    pub fn records_raw(self) -> RecordsRaw<R> {
+        RecordsRaw { reader: self }
+    }
+
+    /// Reads the next record's exact bytes, see [`WPILOGReader::records_raw()`].
+    fn read_record_raw(&mut self) -> Result<Option<Box<[u8]>>> {
+        let Some(header) = self.try_read_record_header()? else {
+            return Ok(None);
+        };
+
+        // The header was already consumed, but each field is just little endian in the width the
+        // bitfield says, so writing them back out gives exactly the original bytes
+        let encoding = header.encoding;
+        let payload = self.read_payload(header.size)?;
+
+        // Sized from what was actually read, `header.size` alone can't be trusted yet
+        let mut raw = Vec::with_capacity(header.length + payload.len());
+        raw.push(encoding.bitfield);
+        raw.extend_from_slice(&le::encode_u32(header.id)[..encoding.entry_len.into()]);
+        raw.extend_from_slice(&le::encode_u64(header.size as u64)[..encoding.size_len.into()]);
+        raw.extend_from_slice(&le::encode_u64(header.timestamp)[..encoding.timestamp_len.into()]);
+        raw.extend_from_slice(&payload);
+
+        Ok(Some(raw.into_boxed_slice()))
+    }
+
+    fn read_record_with_encoding(&mut self) -> Result<Option<(PlainRecord, RecordEncoding)>> {
+        let Some(header) = self.try_read_record_header()? else {
+            return Ok(None);
+        };
+
+        let data = self.read_payload(header.size)?.into_boxed_slice();
+
+        Ok(Some((
+            PlainRecord {
+                id: header.id,
+                timestamp: header.timestamp,
+                data,
+            },
+            header.encoding,
+        )))
+    }
+
+    /// Reads a record's payload. The size comes from the (possibly corrupt) header, so the buffer
+    /// only grows as data actually shows up instead of being allocated up front.
+    fn read_payload(&mut self, size: usize) -> Result<Vec<u8>> {
+        let mut data = vec![];
+        self.read_payload_into(size, &mut data)?;
+
+        Ok(data)
+    }
+
+    /// Like [`WPILOGReader::read_payload()`], but reads into `data` (replacing what it had) so its
+    /// allocation can be reused.
+    fn read_payload_into(&mut self, size: usize, data: &mut Vec<u8>) -> Result<()> {
+        const PREALLOCATE_LIMIT: usize = 64 * 1024;
+
+        data.clear();
+        data.reserve(size.min(PREALLOCATE_LIMIT));
+        (&mut self.reader).take(size as u64).read_to_end(data)?;
+
+        if data.len() != size {
+            return Err(WpilogError::Truncated(format!(
+                "record payload, its size is {size} bytes but only {} are left",
+                data.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reads everything about the next record except its data.
+    fn read_record_header(&mut self) -> Option<RecordHeader> {
         // TODO: actually check what the error is
-        if let Err(_err) = self.reader.read_exact(&mut bitfield) {
-            return None;
+        self.try_read_record_header().ok().flatten()
+    }
+
+    /// Reads everything about the next record except its data, `Ok(None)` means the log is over.
+    fn try_read_record_header(&mut self) -> Result<Option<RecordHeader>> {
+        let mut bitfield = [0; 1];
+
+        if self.reader.read(&mut bitfield)? == 0 {
+            return Ok(None);
         }
 
         let bitfield = bitfield[0];
@@ -90,29 +546,717 @@ impl<R: Read> Iterator for WPILOGReader<R> {
 
         // Entry has to be a u32 or smaller since the bitfield can only represent byte lengths of 1-4
         #[allow(clippy::cast_possible_truncation)]
-        let entry = self.read_variable_int_option(entry_length.into())? as u32;
+        let id = self.read_variable_int(entry_length.into())? as u32;
         // Entry has to be a u32 or smaller since the bitfield can only represent byte lengths of 1-4
         // This code doesn't target lower than 32 bit systems so this cast will always be safe
         #[allow(clippy::cast_possible_truncation)]
-        let size = self.read_variable_int_option(size_length.into())? as usize;
+        let size = self.read_variable_int(size_length.into())? as usize;
+
+        if size > self.max_record_size {
+            return Err(WpilogError::InvalidRecord(format!(
+                "Record payload is {size} bytes, over the limit of {} bytes",
+                self.max_record_size
+            )));
+        }
+
+        let timestamp = self.read_variable_int(timestamp_length.into())?;
+
+        Ok(Some(RecordHeader {
+            id,
+            size,
+            timestamp,
+            length: 1 + usize::from(entry_length + size_length + timestamp_length),
+            encoding: RecordEncoding {
+                bitfield,
+                entry_len: entry_length,
+                size_len: size_length,
+                timestamp_len: timestamp_length,
+            },
+        }))
+    }
+
+    fn read_variable_int(&mut self, length: usize) -> Result<u64> {
+        if !(1..=8).contains(&length) {
+            return Err(WpilogError::InvalidRecord(format!(
+                "Invalid variable int length {length}"
+            )));
+        }
+
+        let mut final_buf: Box<[u8; 8]> = Box::from([0; 8]);
+        self.reader.read_exact(&mut final_buf[0..length])?;
+
+        Ok(le::decode_u64(*final_buf))
+    }
+}
 
-        let timestamp = self.read_variable_int_option(timestamp_length.into())?;
+/// Reads `data` as a log as far as it can, through both [`WPILOGReader`] and
+/// [`SliceReader`](crate::slice::SliceReader), decoding every record along the way.
+///
+/// Any error just ends (or skips) what it was reading, so this is the entry point for fuzzing: no
+/// input should make it panic or allocate unreasonably. Returns how many data records decoded
+/// successfully with [`WPILOGReader`].
+#[must_use]
+pub fn read_all_lossy(data: &[u8]) -> usize {
+    let mut decoded = 0;
 
-        let mut data = vec![0; size].into_boxed_slice();
+    if let Ok(reader) = WPILOGReader::new_raw(data) {
+        let mut schema = SchemaReader::new(reader);
 
+        while let Some(record) = schema.next() {
+            let Ok(record) = record else {
+                continue;
+            };
+            let RecordInfo::Data(payload) = &record.info else {
+                continue;
+            };
+
+            let valid = schema
+                .entry(record.id)
+                .is_some_and(|entry| DecodedValue::decode(&entry.r#type, payload).is_ok());
+            if valid {
+                decoded += 1;
+            }
+        }
+    }
+
+    if let Ok(mut reader) = crate::slice::SliceReader::new(data) {
+        while let Ok(Some(record)) = reader.read_record() {
+            let _ = Record::try_from(PlainRecord {
+                id: record.id,
+                timestamp: record.timestamp,
+                data: record.data.into(),
+            });
+        }
+    }
+
+    decoded
+}
+
+/// Whether a control record's payload is a `Start` for the [`checksum::ENTRY_NAME`] entry, looking
+/// only at the name so nothing else has to be valid.
+fn names_checksum_entry(data: &[u8]) -> bool {
+    let name = checksum::ENTRY_NAME.as_bytes();
+
+    // Kind, entry id, then the length prefixed name
+    data.first() == Some(&0)
+        && le::read_u32(data, 5).is_some_and(|length| length as usize == name.len())
+        && data.get(9..9 + name.len()) == Some(name)
+}
+
+/// What [`WPILOGReader::verify_checksum()`] returns when a record can't be read: a log that doesn't
+/// even parse can't match its checksum, but other I/O errors are still errors.
+fn damaged(err: WpilogError) -> Result<bool> {
+    match err {
+        WpilogError::Truncated(_) | WpilogError::InvalidRecord(_) => Ok(false),
+        WpilogError::Io(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        err => Err(err),
+    }
+}
+
+/// Grows a `(min, max)` timestamp span to include `timestamp`.
+fn widen(span: Option<(u64, u64)>, timestamp: u64) -> (u64, u64) {
+    span.map_or((timestamp, timestamp), |(min, max)| {
+        (min.min(timestamp), max.max(timestamp))
+    })
+}
+
+impl<R: Read> Iterator for WPILOGReader<R> {
+    type Item = PlainRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
         // TODO: actually check what the error is
-        if let Err(_err) = self.reader.read_exact(&mut data) {
-            return None;
+        self.read_record().ok().flatten()
+    }
+}
+
+impl<R: Read + Seek> WPILOGReader<R> {
+    /// Goes back to the first record, so the log can be read again without a new reader.
+    ///
+    /// This assumes the log starts at offset 0 of the reader. Readers that can't seek don't have
+    /// this method at all, make a new [`WPILOGReader`] for those instead.
+    pub fn rewind(&mut self) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(self.records_start))?;
+        Ok(())
+    }
+
+    /// Scans every record from the start of the log, returning where each one starts.
+    ///
+    /// Afterwards the reader is back at the first record. The index is also kept around for
+    /// [`WPILOGReader::seek_to_timestamp()`]. This assumes the log starts at offset 0 of the reader.
+    pub fn build_index(&mut self) -> Result<&[IndexEntry]> {
+        self.rewind()?;
+
+        let mut index = vec![];
+        let mut offset = self.records_start;
+
+        while let Some(header) = self.read_record_header() {
+            let skipped = std::io::copy(
+                &mut (&mut self.reader).take(header.size as u64),
+                &mut std::io::sink(),
+            )?;
+
+            // A truncated record at the end can't be read anyways, so leave it out
+            if skipped != header.size as u64 {
+                break;
+            }
+
+            index.push(IndexEntry {
+                offset,
+                timestamp: header.timestamp,
+            });
+            offset += (header.length + header.size) as u64;
+        }
+
+        self.rewind()?;
+
+        Ok(self.index.insert(index.into_boxed_slice()))
+    }
+
+    /// Moves the reader to the first record with a timestamp `>= timestamp`, building the index if needed.
+    ///
+    /// This is a binary search, so it expects the records to be sorted by timestamp like
+    /// `WPILib` writes them. If there is no such record, the reader is moved to the end.
+    pub fn seek_to_timestamp(&mut self, timestamp: u64) -> Result<()> {
+        if self.index.is_none() {
+            self.build_index()?;
+        }
+
+        let index = self.index.as_deref().unwrap_or_default();
+        let position = index.partition_point(|entry| entry.timestamp < timestamp);
+
+        match index.get(position) {
+            Some(entry) => self.reader.seek(SeekFrom::Start(entry.offset))?,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+
+        Ok(())
+    }
+    /// Yields every record from the last to the first, for showing the most recent records first.
+    ///
+    /// Only seekable readers have this, since it jumps to each record using the index from
+    /// [`WPILOGReader::build_index()`] (built first if it hasn't been). Like the forward iterator, it
+    /// ends at the first record that can't be read.
+    pub fn records_rev(mut self) -> Result<RecordsRev<R>> {
+        if self.index.is_none() {
+            self.build_index()?;
+        }
+
+        let remaining = self.index.as_deref().unwrap_or_default().len();
+        Ok(RecordsRev {
+            reader: self,
+            remaining,
+        })
+    }
+
+    /// Recomputes the CRC32 written by [`WPILOGWriterBuilder::checksum()`] and compares it to the trailer.
+    ///
+    /// Afterwards the reader is back at the first record.
+    ///
+    /// A log too damaged to read to the end, or with a malformed trailer, doesn't match (`Ok(false)`).
+    ///
+    /// # Errors
+    /// If the log doesn't have a checksum trailer, or reading fails for some other reason than the
+    /// log's contents.
+    ///
+    /// [`WPILOGWriterBuilder::checksum()`]: crate::writer::WPILOGWriterBuilder::checksum
+    pub fn verify_checksum(&mut self) -> Result<bool> {
+        self.rewind()?;
+        let matches = self.compare_checksum();
+        self.rewind()?;
+
+        matches
+    }
+
+    /// [`WPILOGReader::verify_checksum()`] from the first record, leaving the reader anywhere.
+    fn compare_checksum(&mut self) -> Result<bool> {
+        let mut offset = self.records_start;
+        let mut trailer = None;
+        let mut expected = None;
+
+        loop {
+            let (header, data) = match self.try_read_record_header() {
+                Ok(Some(header)) => match self.read_payload(header.size) {
+                    Ok(data) => (header, data),
+                    Err(err) => return damaged(err),
+                },
+                Ok(None) => break,
+                Err(err) => return damaged(err),
+            };
+
+            if header.id == 0 {
+                // Only a Start naming the checksum entry is decoded, other control records don't matter
+                if !names_checksum_entry(&data) {
+                    offset += (header.length + header.size) as u64;
+                    continue;
+                }
+
+                let Ok(record) = Record::try_from(PlainRecord {
+                    id: header.id,
+                    timestamp: header.timestamp,
+                    data: data.into_boxed_slice(),
+                }) else {
+                    return Ok(false);
+                };
+
+                trailer = Some((record.id, offset));
+                expected = None;
+            } else if trailer.is_some_and(|(id, _)| id == header.id) {
+                let Ok(bytes) = <[u8; 8]>::try_from(&*data) else {
+                    return Ok(false);
+                };
+                expected = Some(le::decode_i64(bytes));
+            }
+
+            offset += (header.length + header.size) as u64;
+        }
+
+        let (Some((_, trailer_offset)), Some(expected)) = (trailer, expected) else {
+            return Err(WpilogError::Missing(
+                "Log doesn't have a checksum trailer".into(),
+            ));
+        };
+
+        self.rewind()?;
+        let mut crc = Crc32::new();
+        std::io::copy(
+            &mut (&mut self.reader).take(trailer_offset - self.records_start),
+            &mut crc,
+        )?;
+
+        Ok(i64::from(crc.finish()) == expected)
+    }
+}
+
+/// Reads several segments of one log (like the sinks from [`WPILOGWriter::rotate()`]) as a single log.
+///
+/// Every segment after the first starts by repeating the `Start` and `SetMetadata` records of the
+/// entries that were still open, those are skipped if they match what's already known about the entry.
+///
+/// [`WPILOGWriter::rotate()`]: crate::writer::WPILOGWriter::rotate
+pub struct ConcatReader<R: Read> {
+    /// The remaining segments, in reverse so the next one can be popped off
+    segments: Vec<WPILOGReader<R>>,
+    current: Option<WPILOGReader<R>>,
+    /// If `current` is the first segment, which has nothing to deduplicate
+    first: bool,
+    open: HashMap<u32, OpenEntry>,
+}
+
+/// A [`ConcatReader`] over the `.wpilog` files in a directory, see [`ConcatReader::open_dir()`].
+pub type MultiFileReader = ConcatReader<BufReader<File>>;
+
+/// How [`ConcatReader::open_dir_sorted()`] orders the files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SegmentOrder {
+    /// By file name, which works for names with a zero padded counter or timestamp
+    #[default]
+    Name,
+    /// By the timestamp of each file's first record, files without any records go last
+    FirstTimestamp,
+}
+
+impl ConcatReader<BufReader<File>> {
+    /// Opens every `*.wpilog` file directly in `path` (not in subdirectories), in name order.
+    pub fn open_dir(path: impl AsRef<Path>) -> Result<MultiFileReader> {
+        ConcatReader::open_dir_sorted(path, SegmentOrder::Name)
+    }
+
+    /// Like [`ConcatReader::open_dir()`], but with a choice of how the files are ordered.
+    pub fn open_dir_sorted(path: impl AsRef<Path>, order: SegmentOrder) -> Result<MultiFileReader> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "wpilog")
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut readers = paths
+            .iter()
+            .map(|path| WPILOGReader::new_buffered(File::open(path)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        if order == SegmentOrder::FirstTimestamp {
+            let mut keyed = vec![];
+            for mut reader in readers {
+                let first = reader.read_record()?.map(|record| record.timestamp);
+                reader.rewind()?;
+                // `None` sorts first, so flip it to put empty files last
+                keyed.push((first.is_none(), first, reader));
+            }
+
+            // Stable, so files with the same first timestamp stay in name order
+            keyed.sort_by_key(|(empty, first, _)| (*empty, *first));
+            readers = keyed.into_iter().map(|(_, _, reader)| reader).collect();
+        }
+
+        Ok(ConcatReader::new(readers))
+    }
+}
+
+/// The `Start` payload and latest `SetMetadata` payload of an open entry
+struct OpenEntry {
+    start: Box<[u8]>,
+    metadata: Option<Box<[u8]>>,
+}
+
+impl<R: Read> ConcatReader<R> {
+    #[must_use]
+    pub fn new(mut readers: Vec<WPILOGReader<R>>) -> ConcatReader<R> {
+        readers.reverse();
+
+        ConcatReader {
+            current: readers.pop(),
+            segments: readers,
+            first: true,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Updates the open entries, returning if the record is a repeat that should be skipped.
+    fn is_repeat(&mut self, record: &PlainRecord) -> bool {
+        if !record.is_control() {
+            return false;
+        }
+
+        let Ok((kind, id)) = ControlData::peek_kind(&record.data) else {
+            return false;
+        };
+
+        match kind {
+            ControlKind::Start => {
+                if !self.first
+                    && self
+                        .open
+                        .get(&id)
+                        .is_some_and(|entry| entry.start == record.data)
+                {
+                    return true;
+                }
+
+                self.open.insert(
+                    id,
+                    OpenEntry {
+                        start: record.data.clone(),
+                        metadata: None,
+                    },
+                );
+            }
+            ControlKind::SetMetadata => {
+                if let Some(entry) = self.open.get_mut(&id) {
+                    if !self.first && entry.metadata.as_ref() == Some(&record.data) {
+                        return true;
+                    }
+
+                    entry.metadata = Some(record.data.clone());
+                }
+            }
+            ControlKind::Finish => {
+                self.open.remove(&id);
+            }
+        }
+
+        false
+    }
+}
+
+impl<R: Read> Iterator for ConcatReader<R> {
+    type Item = PlainRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(record) = self.current.as_mut()?.next() else {
+                self.current = self.segments.pop();
+                self.first = false;
+                continue;
+            };
+
+            if !self.is_repeat(&record) {
+                return Some(record);
+            }
+        }
+    }
+}
+
+/// A [`Read`] that waits for more data instead of reporting an EOF, see [`WPILOGReader::records_tailing()`].
+pub struct TailReader<R: Read> {
+    inner: R,
+    poll_interval: Duration,
+}
+
+impl<R: Read> Read for TailReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.inner.read(buf) {
+                Ok(0) if !buf.is_empty() => thread::sleep(self.poll_interval),
+                res => return res,
+            }
+        }
+    }
+}
+
+/// How much of a log one entry took up, see [`WPILOGReader::size_breakdown()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntrySize {
+    /// Number of data records
+    pub records: u64,
+    /// Total payload bytes, not counting record headers
+    pub bytes: u64,
+}
+
+/// All records sharing a single timestamp, see [`WPILOGReader::frames()`].
+#[derive(Debug)]
+pub struct Frame {
+    pub timestamp: u64,
+    pub records: Vec<PlainRecord>,
+}
+
+/// Iterator returned by [`WPILOGReader::frames()`].
+pub struct Frames<R: Read> {
+    reader: WPILOGReader<R>,
+    /// The first record of the next frame, read while looking for the end of the current one
+    pending: Option<PlainRecord>,
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.pending.take().or_else(|| self.reader.next())?;
+
+        let mut frame = Frame {
+            timestamp: first.timestamp,
+            records: vec![first],
+        };
+
+        for record in self.reader.by_ref() {
+            if record.timestamp != frame.timestamp {
+                self.pending = Some(record);
+                break;
+            }
+
+            frame.records.push(record);
+        }
+
+        Some(frame)
+    }
+}
+
+/// Iterator returned by [`WPILOGReader::changes_only()`].
+pub struct ChangesOnly<R: Read> {
+    reader: WPILOGReader<R>,
+    /// The last payload kept for each entry id
+    last: HashMap<u32, Box<[u8]>>,
+}
+
+impl<R: Read> Iterator for ChangesOnly<R> {
+    type Item = PlainRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for record in self.reader.by_ref() {
+            if record.is_control() {
+                if let Ok((ControlKind::Start, id)) = ControlData::peek_kind(&record.data) {
+                    self.last.remove(&id);
+                }
+
+                return Some(record);
+            }
+
+            if self.last.get(&record.id) == Some(&record.data) {
+                continue;
+            }
+
+            self.last.insert(record.id, record.data.clone());
+            return Some(record);
+        }
+
+        None
+    }
+}
+
+/// Iterator returned by [`WPILOGReader::boolean_edges()`].
+pub struct BooleanEdges<R: Read> {
+    reader: WPILOGReader<R>,
+    id: u32,
+    last: Option<bool>,
+}
+
+impl<R: Read> Iterator for BooleanEdges<R> {
+    type Item = (u64, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for record in self.reader.by_ref() {
+            if record.is_control() {
+                if ControlData::peek_kind(&record.data)
+                    .is_ok_and(|kind| kind == (ControlKind::Start, self.id))
+                {
+                    self.last = None;
+                }
+
+                continue;
+            }
+
+            if record.id != self.id {
+                continue;
+            }
+
+            let Ok(level) = decode_bool(&record.data) else {
+                continue;
+            };
+
+            if self.last != Some(level) {
+                self.last = Some(level);
+                return Some((record.timestamp, level));
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterator returned by [`WPILOGReader::reassembled()`].
+pub struct Reassembled<R: Read, F: FnMut(&PlainRecord) -> bool> {
+    reader: WPILOGReader<R>,
+    is_continuation: F,
+    /// The start of the next record, read while looking for the end of the current one
+    pending: Option<PlainRecord>,
+}
+
+impl<R: Read, F: FnMut(&PlainRecord) -> bool> Iterator for Reassembled<R, F> {
+    type Item = PlainRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.pending.take().or_else(|| self.reader.next())?;
+        if first.is_control() {
+            return Some(first);
+        }
+
+        let mut data = Vec::from(first.data);
+
+        for record in self.reader.by_ref() {
+            if record.id != first.id || !(self.is_continuation)(&record) {
+                self.pending = Some(record);
+                break;
+            }
+
+            data.extend_from_slice(&record.data);
         }
 
         Some(PlainRecord {
-            id: entry,
-            timestamp,
-            data,
+            id: first.id,
+            timestamp: first.timestamp,
+            data: data.into_boxed_slice(),
         })
     }
 }
 
+struct RecordHeader {
+    id: u32,
+    size: usize,
+    timestamp: u64,
+    /// How many bytes the header itself took up
+    length: usize,
+    encoding: RecordEncoding,
+}
+
+/// How a record's header was encoded, see [`WPILOGReader::records_with_encoding()`].
+///
+/// The lengths are in bytes. An encoder is only as compact as possible if each length is the
+/// smallest one that fits the value, but using more bytes is still valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordEncoding {
+    /// The first byte of the record, which the lengths are decoded from
+    pub bitfield: u8,
+    pub entry_len: u8,
+    pub size_len: u8,
+    pub timestamp_len: u8,
+}
+
+/// Iterator returned by [`WPILOGReader::records_with_encoding()`].
+pub struct RecordsWithEncoding<R: Read> {
+    reader: WPILOGReader<R>,
+}
+
+impl<R: Read> Iterator for RecordsWithEncoding<R> {
+    type Item = (PlainRecord, RecordEncoding);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // TODO: actually check what the error is
+        self.reader.read_record_with_encoding().ok().flatten()
+    }
+}
+
+/// Iterator returned by [`WPILOGReader::records_raw()`].
+pub struct RecordsRaw<R: Read> {
+    reader: WPILOGReader<R>,
+}
+
+impl<R: Read> Iterator for RecordsRaw<R> {
+    type Item = Box<[u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // TODO: actually check what the error is
+        self.reader.read_record_raw().ok().flatten()
+    }
+}
+
+/// Iterator returned by [`WPILOGReader::records_rev()`].
+pub struct RecordsRev<R: Read + Seek> {
+    reader: WPILOGReader<R>,
+    /// How many records are left, the next one is at this index minus one
+    remaining: usize,
+}
+
+impl<R: Read + Seek> Iterator for RecordsRev<R> {
+    type Item = PlainRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+
+        let offset = self.reader.index.as_deref()?[self.remaining].offset;
+        let record = self
+            .reader
+            .reader
+            .seek(SeekFrom::Start(offset))
+            .ok()
+            .and_then(|_| self.reader.read_record().ok().flatten());
+
+        if record.is_none() {
+            self.remaining = 0;
+        }
+
+        record
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// Where a record starts in the log, see [`WPILOGReader::build_index()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub offset: u64,
+    pub timestamp: u64,
+}
+
+/// A [`PlainRecord`] borrowing its data, see [`WPILOGReader::for_each_record()`].
+#[derive(Debug, Clone, Copy)]
+pub struct PlainRecordRef<'a> {
+    pub id: u32,
+    pub timestamp: u64,
+    pub data: &'a [u8],
+}
+
+/// A record straight from the log, with its payload still encoded.
+///
+/// Records with id 0 are control records (`Start`, `Finish` or `SetMetadata`), and the entry they
+/// are about is inside their payload. Every other id is a data record for the entry with that id.
 #[derive(Debug)]
 pub struct PlainRecord {
     pub id: u32,
@@ -120,50 +1264,52 @@ pub struct PlainRecord {
     pub data: Box<[u8]>,
 }
 
+impl PlainRecord {
+    /// If this is a control record, meaning its id is 0.
+    #[must_use]
+    pub fn is_control(&self) -> bool {
+        self.id == 0
+    }
+
+    /// If this is a data record, meaning its id isn't 0.
+    #[must_use]
+    pub fn is_data(&self) -> bool {
+        self.id != 0
+    }
+}
+
 impl TryFrom<PlainRecord> for Record {
-    type Error = anyhow::Error;
+    type Error = WpilogError;
 
+    #[allow(clippy::too_many_lines)]
     fn try_from(record: PlainRecord) -> std::result::Result<Self, Self::Error> {
-        if record.id == 0 {
+        if record.is_control() {
             let mut ptr = 0;
 
             if record.data.is_empty() {
-                return Err(format_err!("Not enough data"));
+                return Err(WpilogError::Truncated("control record type".into()));
             }
 
             let rtype = record.data[ptr];
 
             ptr += 1;
 
-            if record.data.len() < ptr + 4 {
-                return Err(format_err!("Not enough data for entry id"));
-            }
-
-            let id = u32::from_le_bytes([
-                record.data[ptr],
-                record.data[ptr + 1],
-                record.data[ptr + 2],
-                record.data[ptr + 3],
-            ]);
+            let Some(id) = le::read_u32(&record.data, ptr) else {
+                return Err(WpilogError::Truncated("entry id".into()));
+            };
             ptr += 4;
 
             let info = match rtype {
                 0 => {
                     let name = {
-                        if record.data.len() < ptr + 4 {
-                            return Err(format_err!("Not enough data for length of entry name"));
-                        }
-
-                        let length = u32::from_le_bytes([
-                            record.data[ptr],
-                            record.data[ptr + 1],
-                            record.data[ptr + 2],
-                            record.data[ptr + 3],
-                        ]) as usize;
+                        let Some(length) = le::read_u32(&record.data, ptr) else {
+                            return Err(WpilogError::Truncated("length of entry name".into()));
+                        };
+                        let length = length as usize;
                         ptr += 4;
 
-                        if record.data.len() < ptr + length {
-                            return Err(format_err!("Not enough data for entry name"));
+                        if record.data.len() - ptr < length {
+                            return Err(WpilogError::Truncated("entry name".into()));
                         }
 
                         let res = str::from_utf8(&record.data[ptr..ptr + length])?
@@ -175,20 +1321,14 @@ impl TryFrom<PlainRecord> for Record {
                     };
 
                     let etype = {
-                        if record.data.len() < ptr + 4 {
-                            return Err(format_err!("Not enough data for length of entry type"));
-                        }
-
-                        let length = u32::from_le_bytes([
-                            record.data[ptr],
-                            record.data[ptr + 1],
-                            record.data[ptr + 2],
-                            record.data[ptr + 3],
-                        ]) as usize;
+                        let Some(length) = le::read_u32(&record.data, ptr) else {
+                            return Err(WpilogError::Truncated("length of entry type".into()));
+                        };
+                        let length = length as usize;
                         ptr += 4;
 
-                        if record.data.len() < ptr + length {
-                            return Err(format_err!("Not enough data for entry type"));
+                        if record.data.len() - ptr < length {
+                            return Err(WpilogError::Truncated("entry type".into()));
                         }
 
                         let res = str::from_utf8(&record.data[ptr..ptr + length])?
@@ -200,22 +1340,14 @@ impl TryFrom<PlainRecord> for Record {
                     };
 
                     let metadata = {
-                        if record.data.len() < ptr + 4 {
-                            return Err(format_err!(
-                                "Not enough data for length of entry metadata"
-                            ));
-                        }
-
-                        let length = u32::from_le_bytes([
-                            record.data[ptr],
-                            record.data[ptr + 1],
-                            record.data[ptr + 2],
-                            record.data[ptr + 3],
-                        ]) as usize;
+                        let Some(length) = le::read_u32(&record.data, ptr) else {
+                            return Err(WpilogError::Truncated("length of entry metadata".into()));
+                        };
+                        let length = length as usize;
                         ptr += 4;
 
-                        if record.data.len() < ptr + length {
-                            return Err(format_err!("Not enough data for entry metadata"));
+                        if record.data.len() - ptr < length {
+                            return Err(WpilogError::Truncated("entry metadata".into()));
                         }
 
                         str::from_utf8(&record.data[ptr..ptr + length])?
@@ -232,22 +1364,14 @@ impl TryFrom<PlainRecord> for Record {
                 1 => ControlData::Finish,
                 2 => {
                     let metadata = {
-                        if record.data.len() < ptr + 4 {
-                            return Err(format_err!(
-                                "Not enough data for length of entry metadata"
-                            ));
-                        }
-
-                        let length = u32::from_le_bytes([
-                            record.data[ptr],
-                            record.data[ptr + 1],
-                            record.data[ptr + 2],
-                            record.data[ptr + 3],
-                        ]) as usize;
+                        let Some(length) = le::read_u32(&record.data, ptr) else {
+                            return Err(WpilogError::Truncated("length of entry metadata".into()));
+                        };
+                        let length = length as usize;
                         ptr += 4;
 
-                        if record.data.len() < ptr + length {
-                            return Err(format_err!("Not enough data for entry metadata"));
+                        if record.data.len() - ptr < length {
+                            return Err(WpilogError::Truncated("entry metadata".into()));
                         }
 
                         str::from_utf8(&record.data[ptr..ptr + length])?
@@ -257,7 +1381,11 @@ impl TryFrom<PlainRecord> for Record {
 
                     ControlData::SetMetadata(metadata)
                 }
-                _ => return Err(format_err!("Invalid Control Record Type: {rtype}")),
+                _ => {
+                    return Err(WpilogError::InvalidRecord(format!(
+                        "Invalid Control Record Type: {rtype}"
+                    )))
+                }
             };
 
             Ok(Record {
@@ -0,0 +1,274 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    error::{Result, WpilogError},
+    reader::PlainRecord,
+    types::WpiType,
+    value::{decode_i64, shape_from_metadata, DecodedValue},
+    ControlData, Record, RecordInfo,
+};
+
+/// The name of the `int64` entry `WPILib`'s `DataLogManager` logs the system (unix) time to, in
+/// microseconds, see [`SchemaReader::system_time_offset()`].
+pub const SYSTEM_TIME_ENTRY: &str = "systemTime";
+
+/// What a `Start` control record said about an entry.
+#[derive(Debug, Clone)]
+pub struct EntryInfo {
+    pub name: Arc<str>,
+    pub r#type: WpiType,
+    /// The latest metadata, replaced by every `SetMetadata` record
+    pub metadata: Box<str>,
+}
+
+/// Decodes records while keeping track of which entry every id belongs to.
+///
+/// Entries stay known after their `Finish` record, so the `Finish` itself (and anything after it) can
+/// still be matched up with a name. A new `Start` for the same id replaces the old entry.
+///
+/// Every record is yielded as a [`Result`], so a malformed one can be handled (or skipped) without
+/// ending the whole read.
+pub struct SchemaReader<I: Iterator<Item = PlainRecord>> {
+    records: I,
+    entries: HashMap<u32, EntryInfo>,
+    /// The ids between their `Start` and `Finish`
+    open: HashSet<u32>,
+    skip_unknown_control: bool,
+    skip_system_time: bool,
+    /// Unix microseconds minus the record timestamp, from the latest system time value
+    system_time_offset: Option<i64>,
+}
+
+impl EntryInfo {
+    /// The shape of a matrix entry, from its metadata (see [`shape_from_metadata()`]).
+    #[must_use]
+    pub fn shape(&self) -> Option<Vec<usize>> {
+        shape_from_metadata(&self.metadata)
+    }
+
+    /// Parses the metadata as JSON, like `{"unit":"m","source":"nt"}`, into `T`.
+    ///
+    /// # Errors
+    /// [`WpilogError::InvalidRecord`] if the metadata isn't valid JSON or doesn't match `T`.
+    #[cfg(feature = "serde_json")]
+    pub fn metadata_as<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.metadata).map_err(|err| {
+            WpilogError::InvalidRecord(format!(
+                "Metadata of entry {} isn't valid JSON for this type: {err}",
+                self.name
+            ))
+        })
+    }
+}
+
+impl<I: Iterator<Item = PlainRecord>> SchemaReader<I> {
+    pub fn new(records: I) -> SchemaReader<I> {
+        SchemaReader {
+            records,
+            entries: HashMap::new(),
+            open: HashSet::new(),
+            skip_unknown_control: false,
+            skip_system_time: false,
+            system_time_offset: None,
+        }
+    }
+
+    /// Silently skips control records of a type this crate doesn't know about, instead of yielding
+    /// an error for each of them. This way logs using control types added to the spec later can
+    /// still be read. Off by default.
+    #[must_use]
+    pub fn skip_unknown_control(mut self, skip: bool) -> Self {
+        self.skip_unknown_control = skip;
+        self
+    }
+
+    /// Leaves the data records of the [`SYSTEM_TIME_ENTRY`] out, so exports only have the robot's own
+    /// entries. They're still used for [`SchemaReader::system_time_offset()`]. Off by default.
+    #[must_use]
+    pub fn skip_system_time(mut self, skip: bool) -> Self {
+        self.skip_system_time = skip;
+        self
+    }
+
+    /// How far the unix time (in microseconds) is ahead of record timestamps, as of the latest value
+    /// logged to the [`SYSTEM_TIME_ENTRY`]. `None` until one has been read, which on a robot is once
+    /// the clock has been set from the driver station or NTP.
+    ///
+    /// The offset can change during a log, since the system time can jump after the first value, so
+    /// it's worth asking again as records are read.
+    #[must_use]
+    pub fn system_time_offset(&self) -> Option<i64> {
+        self.system_time_offset
+    }
+
+    /// Converts a record timestamp to unix microseconds with [`SchemaReader::system_time_offset()`].
+    #[must_use]
+    pub fn system_time(&self, timestamp: u64) -> Option<i64> {
+        i64::try_from(timestamp)
+            .ok()?
+            .checked_add(self.system_time_offset?)
+    }
+
+    /// The entry with this id, as of the last record read.
+    #[must_use]
+    pub fn entry(&self, id: u32) -> Option<&EntryInfo> {
+        self.entries.get(&id)
+    }
+
+    /// Whether the entry with this id has been started and not finished yet, as of the last record
+    /// read. A data record for an id that isn't open came before its `Start` or after its `Finish`.
+    #[must_use]
+    pub fn is_open(&self, id: u32) -> bool {
+        self.open.contains(&id)
+    }
+
+    /// The entry's metadata as of the last record read, including `SetMetadata` updates.
+    #[must_use]
+    pub fn current_metadata(&self, id: u32) -> Option<&str> {
+        self.entries.get(&id).map(|entry| &*entry.metadata)
+    }
+
+    /// Every entry seen so far.
+    #[must_use]
+    pub fn entries(&self) -> &HashMap<u32, EntryInfo> {
+        &self.entries
+    }
+
+    /// Only yields data records, decoded according to their entry's type.
+    pub fn typed(self) -> TypedRecords<I> {
+        TypedRecords { schema: self }
+    }
+}
+
+impl<I: Iterator<Item = PlainRecord>> SchemaReader<I> {
+    /// Updates the system time offset if this is a system time data record, returning whether it is.
+    fn check_system_time(&mut self, record: &PlainRecord) -> bool {
+        let is_system_time = self.entries.get(&record.id).is_some_and(|entry| {
+            &*entry.name == SYSTEM_TIME_ENTRY && entry.r#type == WpiType::Int64
+        });
+
+        if is_system_time {
+            let offset = decode_i64(&record.data)
+                .ok()
+                .and_then(|unix| unix.checked_sub(i64::try_from(record.timestamp).ok()?));
+
+            // A malformed value is still yielded (or skipped) like any other record
+            if offset.is_some() {
+                self.system_time_offset = offset;
+            }
+        }
+
+        is_system_time
+    }
+}
+
+impl<I: Iterator<Item = PlainRecord>> Iterator for SchemaReader<I> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = self.records.next()?;
+
+        loop {
+            let skip = if record.is_control() {
+                self.skip_unknown_control && record.data.first().is_some_and(|&kind| kind > 2)
+            } else {
+                self.check_system_time(&record) && self.skip_system_time
+            };
+
+            if !skip {
+                break;
+            }
+            record = self.records.next()?;
+        }
+
+        let record = match Record::try_from(record) {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+
+        match &record.info {
+            RecordInfo::Control(ControlData::Start {
+                name,
+                r#type,
+                metadata,
+            }) => {
+                self.entries.insert(
+                    record.id,
+                    EntryInfo {
+                        name: name.as_ref().into(),
+                        r#type: WpiType::from_type_str(r#type),
+                        metadata: metadata.clone(),
+                    },
+                );
+                self.open.insert(record.id);
+            }
+            RecordInfo::Control(ControlData::Finish) => {
+                self.open.remove(&record.id);
+            }
+            RecordInfo::Control(ControlData::SetMetadata(metadata)) => {
+                if let Some(entry) = self.entries.get_mut(&record.id) {
+                    entry.metadata = metadata.clone();
+                }
+            }
+            _ => {}
+        }
+
+        Some(Ok(record))
+    }
+}
+
+/// A decoded data record, see [`SchemaReader::typed()`].
+#[derive(Debug, Clone)]
+pub struct TypedRecord {
+    pub timestamp: u64,
+    pub name: Arc<str>,
+    pub value: DecodedValue,
+}
+
+/// Iterator returned by [`SchemaReader::typed()`].
+pub struct TypedRecords<I: Iterator<Item = PlainRecord>> {
+    schema: SchemaReader<I>,
+}
+
+impl<I: Iterator<Item = PlainRecord>> TypedRecords<I> {
+    /// The [`SchemaReader`] doing the bookkeeping, to look up more about an entry.
+    #[must_use]
+    pub fn schema(&self) -> &SchemaReader<I> {
+        &self.schema
+    }
+}
+
+impl<I: Iterator<Item = PlainRecord>> Iterator for TypedRecords<I> {
+    type Item = Result<TypedRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = match self.schema.next()? {
+                Ok(record) => record,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let RecordInfo::Data(data) = record.info else {
+                continue;
+            };
+
+            let Some(entry) = self.schema.entry(record.id) else {
+                return Some(Err(WpilogError::InvalidRecord(format!(
+                    "Data record for entry {} before its Start record",
+                    record.id
+                ))));
+            };
+
+            return Some(
+                DecodedValue::decode(&entry.r#type, &data).map(|value| TypedRecord {
+                    timestamp: record.timestamp,
+                    name: Arc::clone(&entry.name),
+                    value,
+                }),
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Reading a log that is already entirely in memory, without copying any record data.
+
+use crate::{
+    error::{Result, WpilogError},
+    le,
+    reader::{PlainRecord, PlainRecordRef, ReaderOptions},
+};
+#[cfg(feature = "rayon")]
+use crate::{
+    schema::TypedRecord, types::WpiType, value::DecodedValue, ControlData, Record, RecordInfo,
+};
+
+/// Reads records straight out of a byte slice, every [`PlainRecordRef`] borrows from it.
+#[derive(Debug, Clone)]
+pub struct SliceReader<'a> {
+    pub extra_header: &'a [u8],
+    /// Everything after the file header
+    records: &'a [u8],
+    /// Where the next record starts in `records`
+    ptr: usize,
+    max_record_size: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<SliceReader<'a>> {
+        SliceReader::with_options(data, &ReaderOptions::default())
+    }
+
+    /// Like [`SliceReader::new()`], but with non default [`ReaderOptions`]. The header is checked
+    /// the same way [`WPILOGReader::with_options()`] does.
+    ///
+    /// [`WPILOGReader::with_options()`]: crate::reader::WPILOGReader::with_options
+    pub fn with_options(data: &'a [u8], options: &ReaderOptions) -> Result<SliceReader<'a>> {
+        let Some(&magic) = data.first_chunk::<6>() else {
+            return Err(WpilogError::InvalidHeader);
+        };
+        let Some(version) = data.get(6..8) else {
+            return Err(WpilogError::Truncated("version".into()));
+        };
+        options
+            .header
+            .check(magic, le::decode_u16([version[0], version[1]]))?;
+
+        let Some(length) = le::read_u32(data, 8) else {
+            return Err(WpilogError::Truncated("length of extra header".into()));
+        };
+        let Some(extra_header) = data.get(12..).and_then(|rest| rest.get(..length as usize)) else {
+            return Err(WpilogError::Truncated("extra header".into()));
+        };
+
+        Ok(SliceReader {
+            extra_header,
+            records: &data[12 + extra_header.len()..],
+            ptr: 0,
+            max_record_size: options.max_record_size,
+        })
+    }
+
+    /// Reads the next record, `Ok(None)` means the log ended cleanly between two records.
+    pub fn read_record(&mut self) -> Result<Option<PlainRecordRef<'a>>> {
+        if self.ptr == self.records.len() {
+            return Ok(None);
+        }
+
+        let (record, length) = parse_record_ref(&self.records[self.ptr..])?;
+        if record.data.len() > self.max_record_size {
+            return Err(WpilogError::InvalidRecord(format!(
+                "Record payload is {} bytes, over the limit of {} bytes",
+                record.data.len(),
+                self.max_record_size
+            )));
+        }
+        self.ptr += length;
+
+        Ok(Some(record))
+    }
+
+    /// Decodes every remaining data record on the rayon thread pool, like
+    /// [`WPILOGReader::typed()`](crate::reader::WPILOGReader::typed) does one by one. The results
+    /// are in the same order the records are in the log.
+    ///
+    /// This does one cheap pass over the records to find where each one starts and which entry it
+    /// belongs to, decoding only the control records, then splits decoding the payloads into
+    /// [`DecodedValue`]s between threads. The reader isn't advanced.
+    ///
+    /// Entries are only known from their `Start` records, so those have to be among the remaining
+    /// records.
+    ///
+    /// # Errors
+    /// If a record is truncated, a control record is malformed, a data record comes before its
+    /// entry's `Start`, or a payload can't be decoded as its entry's type.
+    #[cfg(feature = "rayon")]
+    pub fn par_records(&self) -> Result<Vec<TypedRecord>> {
+        use rayon::prelude::*;
+        use std::{collections::HashMap, sync::Arc};
+
+        let mut entries: HashMap<u32, Arc<(Arc<str>, WpiType)>> = HashMap::new();
+        let mut data_records = vec![];
+
+        let mut reader = self.clone();
+        while let Some(record) = reader.read_record()? {
+            if record.id != 0 {
+                let Some(entry) = entries.get(&record.id) else {
+                    return Err(WpilogError::InvalidRecord(format!(
+                        "Data record for entry {} before its Start record",
+                        record.id
+                    )));
+                };
+                data_records.push((record, Arc::clone(entry)));
+                continue;
+            }
+
+            // Entries stay known after their `Finish`, like with `SchemaReader`
+            let control = PlainRecord {
+                id: 0,
+                timestamp: record.timestamp,
+                data: record.data.into(),
+            };
+            let control = Record::try_from(control)?;
+            if let RecordInfo::Control(ControlData::Start { name, r#type, .. }) = control.info {
+                let entry = (name.as_ref().into(), WpiType::from_type_str(&r#type));
+                entries.insert(control.id, Arc::new(entry));
+            }
+        }
+
+        data_records
+            .into_par_iter()
+            .map(|(record, entry)| {
+                let (name, r#type) = &*entry;
+                Ok(TypedRecord {
+                    timestamp: record.timestamp,
+                    name: Arc::clone(name),
+                    value: DecodedValue::decode(r#type, record.data)?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<'a> Iterator for SliceReader<'a> {
+    type Item = PlainRecordRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // TODO: actually check what the error is
+        self.read_record().ok().flatten()
+    }
+}
+
+/// Parses the record at the start of `data`, returning it and how many bytes it took up, or
+/// `Ok(None)` if `data` ends before the record does.
+///
+/// For parsing a log as it arrives in chunks: append to a buffer, then parse records off the front
+/// of it until this asks for more. `data` has to start at a record, so skip the file header (12
+/// bytes plus the extra header) first.
+pub fn parse_record(data: &[u8]) -> Result<Option<(PlainRecord, usize)>> {
+    match parse_record_ref(data) {
+        Ok((record, length)) => Ok(Some((
+            PlainRecord {
+                id: record.id,
+                timestamp: record.timestamp,
+                data: record.data.into(),
+            },
+            length,
+        ))),
+        Err(WpilogError::Truncated(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parses the record at the start of `data`, returning it and how many bytes it took up.
+pub(crate) fn parse_record_ref(data: &[u8]) -> Result<(PlainRecordRef<'_>, usize)> {
+    let (id, size, timestamp, length) = parse_header(data)?;
+
+    let Some(payload) = data.get(length..).and_then(|rest| rest.get(..size)) else {
+        return Err(WpilogError::Truncated("record".into()));
+    };
+
+    Ok((
+        PlainRecordRef {
+            id,
+            timestamp,
+            data: payload,
+        },
+        length + size,
+    ))
+}
+
+/// Parses a record header into `(id, size, timestamp, header length)`.
+fn parse_header(data: &[u8]) -> Result<(u32, usize, u64, usize)> {
+    let Some(&bitfield) = data.first() else {
+        return Err(WpilogError::Truncated("record bitfield".into()));
+    };
+
+    let entry_length = usize::from(bitfield & 0x3) + 1;
+    let size_length = usize::from((bitfield >> 2) & 0x3) + 1;
+    let timestamp_length = usize::from((bitfield >> 4) & 0x7) + 1;
+
+    let mut ptr = 1;
+    let mut read_int = |length: usize| {
+        let Some(bytes) = data.get(ptr..ptr + length) else {
+            return Err(WpilogError::Truncated("record header".into()));
+        };
+        ptr += length;
+
+        let mut buf = [0; 8];
+        buf[..length].copy_from_slice(bytes);
+        Ok(le::decode_u64(buf))
+    };
+
+    // Entry has to be a u32 or smaller since the bitfield can only represent byte lengths of 1-4
+    #[allow(clippy::cast_possible_truncation)]
+    let id = read_int(entry_length)? as u32;
+    // Entry has to be a u32 or smaller since the bitfield can only represent byte lengths of 1-4
+    // This code doesn't target lower than 32 bit systems so this cast will always be safe
+    #[allow(clippy::cast_possible_truncation)]
+    let size = read_int(size_length)? as usize;
+    let timestamp = read_int(timestamp_length)?;
+
+    Ok((
+        id,
+        size,
+        timestamp,
+        1 + entry_length + size_length + timestamp_length,
+    ))
+}
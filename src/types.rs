@@ -0,0 +1,123 @@
+use std::{borrow::Cow, convert::Infallible, fmt::Display, str::FromStr};
+
+/// The type strings `WPILib` uses for entries, as found in [`ControlData::Start`](crate::ControlData::Start).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WpiType {
+    Raw,
+    Boolean,
+    Int64,
+    Float,
+    Double,
+    String,
+    BooleanArray,
+    Int64Array,
+    FloatArray,
+    DoubleArray,
+    StringArray,
+    Json,
+    Msgpack,
+    /// `struct:<name>`, the payload is described by the `/.schema/struct:<name>` entry
+    Struct(Box<str>),
+    /// `struct:<name>[]`
+    StructArray(Box<str>),
+    /// `proto:<name>`
+    Proto(Box<str>),
+    /// Anything else, kept as-is
+    Other(Box<str>),
+}
+
+impl WpiType {
+    /// Parses a type string, anything unknown becomes [`WpiType::Other`].
+    #[must_use]
+    pub fn from_type_str(type_str: &str) -> WpiType {
+        match type_str {
+            "raw" => WpiType::Raw,
+            "boolean" => WpiType::Boolean,
+            "int64" => WpiType::Int64,
+            "float" => WpiType::Float,
+            "double" => WpiType::Double,
+            "string" => WpiType::String,
+            "boolean[]" => WpiType::BooleanArray,
+            "int64[]" => WpiType::Int64Array,
+            "float[]" => WpiType::FloatArray,
+            "double[]" => WpiType::DoubleArray,
+            "string[]" => WpiType::StringArray,
+            "json" => WpiType::Json,
+            "msgpack" => WpiType::Msgpack,
+            _ => {
+                if let Some(name) = type_str.strip_prefix("struct:") {
+                    if let Some(name) = name.strip_suffix("[]") {
+                        WpiType::StructArray(name.into())
+                    } else {
+                        WpiType::Struct(name.into())
+                    }
+                } else if let Some(name) = type_str.strip_prefix("proto:") {
+                    WpiType::Proto(name.into())
+                } else {
+                    WpiType::Other(type_str.into())
+                }
+            }
+        }
+    }
+
+    /// The type string as it's written in the log.
+    #[must_use]
+    pub fn as_type_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(match self {
+            WpiType::Raw => "raw",
+            WpiType::Boolean => "boolean",
+            WpiType::Int64 => "int64",
+            WpiType::Float => "float",
+            WpiType::Double => "double",
+            WpiType::String => "string",
+            WpiType::BooleanArray => "boolean[]",
+            WpiType::Int64Array => "int64[]",
+            WpiType::FloatArray => "float[]",
+            WpiType::DoubleArray => "double[]",
+            WpiType::StringArray => "string[]",
+            WpiType::Json => "json",
+            WpiType::Msgpack => "msgpack",
+            WpiType::Struct(name) => return Cow::Owned(format!("struct:{name}")),
+            WpiType::StructArray(name) => return Cow::Owned(format!("struct:{name}[]")),
+            WpiType::Proto(name) => return Cow::Owned(format!("proto:{name}")),
+            WpiType::Other(type_str) => type_str,
+        })
+    }
+}
+
+/// The log type string for a `NetworkTables` 4 type string.
+///
+/// They're the same except that NT calls integers `int`, so everything else (including `struct:` and
+/// `proto:` types) is passed through unchanged.
+#[must_use]
+pub fn nt_type_to_wpilog(nt_type: &str) -> &str {
+    match nt_type {
+        "int" => "int64",
+        "int[]" => "int64[]",
+        _ => nt_type,
+    }
+}
+
+/// The `NetworkTables` 4 type string for a log type string, the reverse of [`nt_type_to_wpilog()`].
+#[must_use]
+pub fn wpilog_type_to_nt(wpilog_type: &str) -> &str {
+    match wpilog_type {
+        "int64" => "int",
+        "int64[]" => "int[]",
+        _ => wpilog_type,
+    }
+}
+
+impl FromStr for WpiType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(WpiType::from_type_str(s))
+    }
+}
+
+impl Display for WpiType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.as_type_str())
+    }
+}
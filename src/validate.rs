@@ -0,0 +1,220 @@
+//! Checking a log for structural problems, see [`validate()`].
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{ErrorKind, Read},
+};
+
+use crate::{
+    error::WpilogError, reader::WPILOGReader, types::WpiType, value::DecodedValue, ControlData,
+    Record, RecordInfo,
+};
+
+/// A single problem found by [`validate()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// A data record for an id that isn't open, either never started or already finished
+    UnknownEntry { id: u32, timestamp: u64 },
+    /// A `Start` for an id that is already open
+    DuplicateStart { id: u32, timestamp: u64 },
+    /// A `Finish` for an id that isn't open
+    FinishWithoutStart { id: u32, timestamp: u64 },
+    /// A `SetMetadata` for an id that isn't open
+    MetadataWithoutStart { id: u32, timestamp: u64 },
+    /// A control record that couldn't be decoded
+    InvalidControl { timestamp: u64, message: String },
+    /// A data record whose payload doesn't match its entry's type
+    InvalidPayload {
+        id: u32,
+        timestamp: u64,
+        message: String,
+    },
+    /// A record with an earlier timestamp than the one before it
+    NonMonotonicTimestamp { previous: u64, timestamp: u64 },
+    /// The log ended in the middle of a record, nothing after it was checked
+    Truncated { message: String },
+    /// A record the reader refused, like one claiming a payload over
+    /// [`ReaderOptions::max_record_size`](crate::reader::ReaderOptions::max_record_size). Nothing
+    /// after it was checked, since where the next record starts can't be trusted.
+    InvalidRecord { message: String },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::UnknownEntry { id, timestamp } => {
+                write!(
+                    f,
+                    "{timestamp}: data record for entry {id}, which isn't open"
+                )
+            }
+            Issue::DuplicateStart { id, timestamp } => {
+                write!(f, "{timestamp}: entry {id} started while already open")
+            }
+            Issue::FinishWithoutStart { id, timestamp } => {
+                write!(f, "{timestamp}: entry {id} finished while not open")
+            }
+            Issue::MetadataWithoutStart { id, timestamp } => {
+                write!(
+                    f,
+                    "{timestamp}: metadata set for entry {id}, which isn't open"
+                )
+            }
+            Issue::InvalidControl { timestamp, message } => {
+                write!(f, "{timestamp}: invalid control record: {message}")
+            }
+            Issue::InvalidPayload {
+                id,
+                timestamp,
+                message,
+            } => write!(f, "{timestamp}: invalid payload for entry {id}: {message}"),
+            Issue::NonMonotonicTimestamp {
+                previous,
+                timestamp,
+            } => write!(f, "{timestamp}: timestamp went backwards from {previous}"),
+            Issue::Truncated { message } => write!(f, "log is truncated: {message}"),
+            Issue::InvalidRecord { message } => write!(f, "invalid record: {message}"),
+        }
+    }
+}
+
+/// Everything [`validate()`] found.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// How many records were read
+    pub records: usize,
+    /// Every problem, in the order they appear in the log
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// If no problems were found.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks a data record's payload against its entry's type.
+///
+/// Unlike [`DecodedValue::decode()`] an empty payload isn't accepted for types that always have
+/// data, since the spec has no such thing. This means the empty records written by
+/// [`RawEntry::log_empty()`](crate::writer::RawEntry::log_empty) are reported too.
+fn check_payload(r#type: &WpiType, data: &[u8]) -> Result<(), String> {
+    let expected = match r#type {
+        WpiType::Boolean => Some(1),
+        WpiType::Float => Some(4),
+        WpiType::Int64 | WpiType::Double => Some(8),
+        _ => None,
+    };
+
+    if let Some(expected) = expected.filter(|&expected| expected != data.len()) {
+        return Err(format!(
+            "{type} payload must be {expected} bytes, got {}",
+            data.len()
+        ));
+    }
+
+    if *r#type == WpiType::StringArray && data.is_empty() {
+        return Err("string[] payload must at least have its 4 byte length".into());
+    }
+
+    DecodedValue::decode(r#type, data)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Reads every remaining record, collecting every structural problem instead of stopping at the first.
+pub fn validate<R: Read>(reader: &mut WPILOGReader<R>) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut open: HashMap<u32, WpiType> = HashMap::new();
+    let mut previous_timestamp = None;
+
+    loop {
+        let record = match reader.read_record() {
+            Ok(Some(record)) => record,
+            Ok(None) => break,
+            Err(err) => {
+                let message = err.to_string();
+                report.issues.push(match err {
+                    WpilogError::Truncated(_) => Issue::Truncated { message },
+                    WpilogError::Io(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                        Issue::Truncated { message }
+                    }
+                    _ => Issue::InvalidRecord { message },
+                });
+                break;
+            }
+        };
+
+        report.records += 1;
+        let id = record.id;
+        let timestamp = record.timestamp;
+
+        if let Some(previous) = previous_timestamp.filter(|previous| *previous > timestamp) {
+            report.issues.push(Issue::NonMonotonicTimestamp {
+                previous,
+                timestamp,
+            });
+        }
+        previous_timestamp = Some(timestamp);
+
+        if record.is_data() {
+            match open.get(&id) {
+                Some(r#type) => {
+                    if let Err(message) = check_payload(r#type, &record.data) {
+                        report.issues.push(Issue::InvalidPayload {
+                            id,
+                            timestamp,
+                            message,
+                        });
+                    }
+                }
+                None => report.issues.push(Issue::UnknownEntry { id, timestamp }),
+            }
+
+            continue;
+        }
+
+        let record = match Record::try_from(record) {
+            Ok(record) => record,
+            Err(err) => {
+                report.issues.push(Issue::InvalidControl {
+                    timestamp,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let RecordInfo::Control(control) = record.info else {
+            unreachable!("Record id 0 always decodes to a control record")
+        };
+        let id = record.id;
+
+        match control {
+            ControlData::Start { r#type, .. } => {
+                if open.insert(id, WpiType::from_type_str(&r#type)).is_some() {
+                    report.issues.push(Issue::DuplicateStart { id, timestamp });
+                }
+            }
+            ControlData::Finish => {
+                if open.remove(&id).is_none() {
+                    report
+                        .issues
+                        .push(Issue::FinishWithoutStart { id, timestamp });
+                }
+            }
+            ControlData::SetMetadata(_) => {
+                if !open.contains_key(&id) {
+                    report
+                        .issues
+                        .push(Issue::MetadataWithoutStart { id, timestamp });
+                }
+            }
+        }
+    }
+
+    report
+}
@@ -0,0 +1,303 @@
+use core::str;
+
+use crate::{
+    error::{Result, WpilogError},
+    le,
+    types::WpiType,
+};
+
+/// A data record's payload, decoded according to its entry's type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedValue {
+    /// `raw` and every type this crate doesn't know how to decode
+    Raw(Box<[u8]>),
+    Boolean(bool),
+    Int64(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    BooleanArray(Vec<bool>),
+    Int64Array(Vec<i64>),
+    FloatArray(Vec<f32>),
+    DoubleArray(Vec<f64>),
+    StringArray(Vec<String>),
+    /// The JSON text, it isn't parsed
+    Json(String),
+    /// The `MessagePack` bytes, they aren't parsed
+    Msgpack(Box<[u8]>),
+    /// A zero length payload for a type that can't be empty, meaning there's no value (see
+    /// [`RawEntry::log_empty()`](crate::writer::RawEntry::log_empty))
+    Empty,
+}
+
+impl DecodedValue {
+    /// Decodes a data record's payload for an entry of type `r#type`.
+    ///
+    /// Types where an empty payload is a normal value, like `raw`, `string` and most arrays, decode
+    /// it as that. For the rest it's [`DecodedValue::Empty`] instead of an error.
+    pub fn decode(r#type: &WpiType, data: &[u8]) -> Result<DecodedValue> {
+        let needs_data = matches!(
+            r#type,
+            WpiType::Boolean
+                | WpiType::Int64
+                | WpiType::Float
+                | WpiType::Double
+                | WpiType::StringArray
+        );
+        if data.is_empty() && needs_data {
+            return Ok(DecodedValue::Empty);
+        }
+
+        Ok(match r#type {
+            WpiType::Boolean => DecodedValue::Boolean(decode_bool(data)?),
+            WpiType::Int64 => DecodedValue::Int64(decode_i64(data)?),
+            WpiType::Float => DecodedValue::Float(decode_f32(data)?),
+            WpiType::Double => DecodedValue::Double(decode_f64(data)?),
+            WpiType::String => DecodedValue::String(decode_string(data)?),
+            WpiType::BooleanArray => DecodedValue::BooleanArray(decode_bool_array(data)),
+            WpiType::Int64Array => DecodedValue::Int64Array(decode_i64_array(data)?),
+            WpiType::FloatArray => DecodedValue::FloatArray(decode_f32_array(data)?),
+            WpiType::DoubleArray => DecodedValue::DoubleArray(decode_f64_array(data)?),
+            WpiType::StringArray => DecodedValue::StringArray(decode_string_array(data)?),
+            WpiType::Json => DecodedValue::Json(decode_string(data)?),
+            WpiType::Msgpack => DecodedValue::Msgpack(data.into()),
+            _ => DecodedValue::Raw(data.into()),
+        })
+    }
+}
+
+/// Splits `data` into `N` byte chunks, erroring if there's any left over.
+fn chunks<'a, const N: usize>(
+    data: &'a [u8],
+    name: &str,
+) -> Result<impl Iterator<Item = [u8; N]> + 'a> {
+    if data.len() % N != 0 {
+        return Err(WpilogError::InvalidRecord(format!(
+            "{name} payload must be a multiple of {N} bytes, got {}",
+            data.len()
+        )));
+    }
+
+    Ok(data.chunks_exact(N).map(|chunk| {
+        let mut buf = [0; N];
+        buf.copy_from_slice(chunk);
+        buf
+    }))
+}
+
+/// Reads exactly `N` bytes.
+fn exact<const N: usize>(data: &[u8], name: &str) -> Result<[u8; N]> {
+    data.try_into().map_err(|_| {
+        WpilogError::InvalidRecord(format!(
+            "{name} payload must be {N} bytes, got {}",
+            data.len()
+        ))
+    })
+}
+
+pub fn decode_bool(data: &[u8]) -> Result<bool> {
+    Ok(exact::<1>(data, "boolean")?[0] != 0)
+}
+
+/// Decodes the single byte `raw` payload written by [`U8Entry`](crate::entrytypes::U8Entry).
+pub fn decode_u8(data: &[u8]) -> Result<u8> {
+    Ok(exact::<1>(data, "u8")?[0])
+}
+
+pub fn decode_i64(data: &[u8]) -> Result<i64> {
+    Ok(le::decode_i64(exact(data, "int64")?))
+}
+
+pub fn decode_f32(data: &[u8]) -> Result<f32> {
+    Ok(le::decode_f32(exact(data, "float")?))
+}
+
+pub fn decode_f64(data: &[u8]) -> Result<f64> {
+    Ok(le::decode_f64(exact(data, "double")?))
+}
+
+pub fn decode_string(data: &[u8]) -> Result<String> {
+    Ok(str::from_utf8(data)?.to_string())
+}
+
+#[must_use]
+pub fn decode_bool_array(data: &[u8]) -> Vec<bool> {
+    data.iter().map(|byte| *byte != 0).collect()
+}
+
+pub fn decode_i64_array(data: &[u8]) -> Result<Vec<i64>> {
+    Ok(chunks(data, "int64[]")?.map(le::decode_i64).collect())
+}
+
+/// How [`decode_int64_array()`] treats payloads written by old versions of this crate, whose
+/// `I64ArrayEntry` wrote 4 bytes per element instead of 8.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Int64ArrayMode {
+    /// Only accept 8 byte elements
+    #[default]
+    Strict,
+    /// If the payload isn't a multiple of 8 bytes but is a multiple of 4, read it as sign extended
+    /// 4 byte elements. A legacy payload with an even number of elements can't be told apart from a
+    /// correct one, so it's still read as 8 byte elements.
+    Lenient,
+}
+
+/// Decodes an `int64[]` payload, optionally recovering ones written with the legacy 4 byte layout.
+pub fn decode_int64_array(data: &[u8], mode: Int64ArrayMode) -> Result<Vec<i64>> {
+    if mode == Int64ArrayMode::Lenient && data.len() % 8 != 0 && data.len() % 4 == 0 {
+        return Ok(chunks(data, "int64[]")?
+            .map(|chunk| i64::from(le::decode_i32(chunk)))
+            .collect());
+    }
+
+    decode_i64_array(data)
+}
+
+pub fn decode_f32_array(data: &[u8]) -> Result<Vec<f32>> {
+    Ok(chunks(data, "float[]")?.map(le::decode_f32).collect())
+}
+
+pub fn decode_f64_array(data: &[u8]) -> Result<Vec<f64>> {
+    Ok(chunks(data, "double[]")?.map(le::decode_f64).collect())
+}
+
+/// Decodes a `string[]` payload, a `u32` count followed by that many length prefixed strings.
+pub fn decode_string_array(data: &[u8]) -> Result<Vec<String>> {
+    split_string_array(data)?
+        .into_iter()
+        .map(|string| Ok(str::from_utf8(string)?.to_string()))
+        .collect()
+}
+
+/// Like [`decode_string_array()`], but invalid UTF-8 in an element is replaced with `U+FFFD` instead
+/// of failing the whole array. The payload still has to be structurally valid.
+pub fn decode_string_array_lossy(data: &[u8]) -> Result<Vec<String>> {
+    Ok(split_string_array(data)?
+        .into_iter()
+        .map(|string| String::from_utf8_lossy(string).into_owned())
+        .collect())
+}
+
+/// Encodes a `string[]` payload, the inverse of [`decode_string_array()`].
+pub fn encode_string_array(data: &[&str]) -> Result<Box<[u8]>> {
+    let Ok(count) = u32::try_from(data.len()) else {
+        return Err(WpilogError::InvalidInput(format!(
+            "Data can have at max {} items",
+            u32::MAX
+        )));
+    };
+
+    let length = 4 + 4 * data.len() + data.iter().map(|string| string.len()).sum::<usize>();
+    let mut dest = Vec::with_capacity(length);
+    dest.extend_from_slice(&le::encode_u32(count));
+
+    for string in data {
+        let Ok(string_len) = u32::try_from(string.len()) else {
+            return Err(WpilogError::InvalidInput(format!(
+                "Strings can be at max {} bytes long",
+                u32::MAX
+            )));
+        };
+
+        dest.extend_from_slice(&le::encode_u32(string_len));
+        dest.extend_from_slice(string.as_bytes());
+    }
+
+    Ok(dest.into_boxed_slice())
+}
+
+/// Splits a `string[]` payload into each element's bytes.
+fn split_string_array(data: &[u8]) -> Result<Vec<&[u8]>> {
+    let Some(count) = le::read_u32(data, 0) else {
+        return Err(WpilogError::Truncated("length of string array".into()));
+    };
+    let count = count as usize;
+    let mut ptr = 4;
+
+    // Every string needs at least 4 bytes, so don't trust the count for the allocation
+    let mut strings = Vec::with_capacity(count.min((data.len() - ptr) / 4));
+
+    for _ in 0..count {
+        let Some(length) = le::read_u32(data, ptr) else {
+            return Err(WpilogError::Truncated("length of string".into()));
+        };
+        let length = length as usize;
+        ptr += 4;
+
+        if data.len() < ptr + length {
+            return Err(WpilogError::Truncated("string".into()));
+        }
+
+        strings.push(&data[ptr..ptr + length]);
+        ptr += length;
+    }
+
+    if ptr != data.len() {
+        return Err(WpilogError::InvalidRecord(
+            "Extra data after string array".into(),
+        ));
+    }
+
+    Ok(strings)
+}
+
+/// Reads the shape from metadata like `{"shape":[3,3]}`, as written by
+/// [`LogWriter::new_matrix_entry()`](crate::entrytypes::LogWriter::new_matrix_entry).
+///
+/// This only looks for the `"shape"` key, so it works without a JSON parser, and other keys can be
+/// in the metadata too.
+#[must_use]
+pub fn shape_from_metadata(metadata: &str) -> Option<Vec<usize>> {
+    const KEY: &str = "\"shape\"";
+
+    let rest = &metadata[metadata.find(KEY)? + KEY.len()..];
+    let rest = rest
+        .trim_start()
+        .strip_prefix(':')?
+        .trim_start()
+        .strip_prefix('[')?;
+    let dims = &rest[..rest.find(']')?];
+
+    if dims.trim().is_empty() {
+        return Some(vec![]);
+    }
+
+    dims.split(',').map(|dim| dim.trim().parse().ok()).collect()
+}
+
+/// Lowercase hex with no separators, the text representation used for `raw` data.
+#[must_use]
+pub fn to_hex(data: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        hex.push(DIGITS[usize::from(byte >> 4)].into());
+        hex.push(DIGITS[usize::from(byte & 0xf)].into());
+    }
+
+    hex
+}
+
+/// Standard (RFC 4648) padded base64, the compact text representation for `raw` data.
+#[must_use]
+pub fn to_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut base64 = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = (u32::from(chunk[0]) << 16)
+            | (u32::from(*chunk.get(1).unwrap_or(&0)) << 8)
+            | u32::from(*chunk.get(2).unwrap_or(&0));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                base64.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3f].into());
+            } else {
+                base64.push('=');
+            }
+        }
+    }
+
+    base64
+}
@@ -1,112 +1,1427 @@
-use anyhow::{format_err, Result};
-use kanal::Sender;
+use kanal::{ReceiveErrorTimeout, Receiver, Sender};
 use std::{
+    any::Any,
+    cmp::{Ordering as CmpOrdering, Reverse},
+    collections::{BTreeMap, BinaryHeap, HashMap, VecDeque},
+    fmt::Display,
     io::Write,
-    sync::atomic::{AtomicU32, Ordering},
-    thread::JoinHandle,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    thread::{JoinHandle, ThreadId},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use crate::{ControlData, Record, RecordInfo, HEADER_STRING, HEADER_VERSION};
-
-const MAX_ONE_BYTE: u64 = 256u64;
-const MAX_TWO_BYTES: u64 = 256u64.pow(2);
-const MAX_THREE_BYTES: u64 = 256u64.pow(3);
-const MAX_FOUR_BYTES: u64 = 256u64.pow(4);
-const MAX_FIVE_BYTES: u64 = 256u64.pow(5);
-const MAX_SIX_BYTES: u64 = 256u64.pow(6);
-const MAX_SEVEN_BYTES: u64 = 256u64.pow(7);
-
-fn encode_int(num: u64) -> Box<[u8]> {
-    if num < MAX_ONE_BYTE {
-        Box::new([num as u8])
-    } else if num < MAX_TWO_BYTES {
-        Box::new((num as u16).to_le_bytes())
-    } else if num < MAX_THREE_BYTES {
-        let tmp = (num as u32).to_le_bytes();
-        Box::new([tmp[0], tmp[1], tmp[2]])
-    } else if num < MAX_FOUR_BYTES {
-        Box::new((num as u32).to_le_bytes())
-    } else if num < MAX_FIVE_BYTES {
-        let tmp = num.to_le_bytes();
-        Box::new([tmp[0], tmp[1], tmp[2], tmp[3], tmp[4]])
-    } else if num < MAX_SIX_BYTES {
-        let tmp = num.to_le_bytes();
-        Box::new([tmp[0], tmp[1], tmp[2], tmp[3], tmp[4], tmp[5]])
-    } else if num < MAX_SEVEN_BYTES {
-        let tmp = num.to_le_bytes();
-        Box::new([tmp[0], tmp[1], tmp[2], tmp[3], tmp[4], tmp[5], tmp[6]])
-    } else {
-        Box::new(num.to_le_bytes())
+use crate::{
+    checksum::{self, Crc32},
+    entrytypes::LogWriter,
+    error::{Result, WpilogError},
+    le,
+    slice::parse_record_ref,
+    ControlData, HeaderFormat, Record, RecordInfo, WALL_CLOCK_PREFIX,
+};
+
+/// A variable length integer, little endian using as few bytes as possible (but at least one).
+///
+/// It's kept on the stack since every record needs a few of these.
+struct EncodedInt {
+    bytes: [u8; 8],
+    length: usize,
+}
+
+impl std::ops::Deref for EncodedInt {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+/// How many bytes [`encode_int()`] uses for `num`.
+fn int_len(num: u64) -> usize {
+    // leading_zeros is at most 64, so this is always 1-8
+    (8 - num.leading_zeros() as usize / 8).max(1)
+}
+
+fn encode_int(num: u64) -> EncodedInt {
+    EncodedInt {
+        bytes: le::encode_u64(num),
+        length: int_len(num),
+    }
+}
+
+/// Errors specific to writing, as opposed to generic I/O or encoding failures.
+///
+/// These are returned as [`WpilogError::Writer`].
+#[derive(Debug)]
+pub enum WriterError {
+    /// The writer has been joined (or its worker has stopped), so anything logged is lost.
+    Closed,
+    /// The worker thread panicked, this contains the panic message if it was a string.
+    WorkerPanicked(String),
+    /// The worker stopped because the underlying writer failed, anything logged after this is lost.
+    Io(std::io::Error),
+    /// Every entry id has been used, ids are never reused so no more entries can be made.
+    TooManyEntries,
+    /// The entry with this id has already been finished, logging more would put records after its
+    /// `Finish` record.
+    EntryFinished(u32),
+    /// An entry was made on another thread than the one that built a
+    /// [deterministic](WPILOGWriterBuilder::deterministic()) writer.
+    WrongThread,
+}
+
+impl Display for WriterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriterError::Closed => write!(f, "The writer has been closed"),
+            WriterError::WorkerPanicked(msg) => write!(f, "The writer thread panicked: {msg}"),
+            WriterError::Io(err) => write!(f, "The writer thread failed to write: {err}"),
+            WriterError::TooManyEntries => {
+                write!(f, "All {} entry ids have been used", u32::MAX)
+            }
+            WriterError::EntryFinished(id) => write!(f, "Entry {id} has already been finished"),
+            WriterError::WrongThread => write!(
+                f,
+                "Entries of a deterministic writer can only be made on the thread that built it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriterError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Locks the mutex, ignoring poisoning since the data it guards can't be left half updated.
+fn lock<V>(mutex: &Mutex<V>) -> MutexGuard<'_, V> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Like [`lock()`], for reading an [`RwLock`].
+fn read<V>(rwlock: &RwLock<V>) -> RwLockReadGuard<'_, V> {
+    rwlock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Like [`lock()`], for writing an [`RwLock`].
+fn write<V>(rwlock: &RwLock<V>) -> RwLockWriteGuard<'_, V> {
+    rwlock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Encodes the record and queues it for the worker.
+///
+/// # Errors
+/// Returns [`WriterError::Closed`] if the worker is no longer receiving.
+fn send_record(queue: &Queue, record: &Record) -> Result<()> {
+    let data = record.encode();
+
+    let msg = match &record.info {
+        RecordInfo::Data(_) => RecvState::Msg(data),
+        RecordInfo::Control(ControlData::Start { .. }) => RecvState::Start(record.id, data),
+        RecordInfo::Control(ControlData::SetMetadata(_)) => RecvState::SetMetadata(record.id, data),
+        RecordInfo::Control(ControlData::Finish) => RecvState::Finish(record.id, data),
+    };
+
+    queue.send(msg)?;
+
+    Ok(())
+}
+
+pub trait TimeProvider {
+    fn get_time(&self) -> u64;
+}
+
+/// Shifts another [`TimeProvider`]'s timestamps by a fixed offset (in microseconds), to line a log
+/// up with an external clock. Timestamps saturate instead of wrapping below 0.
+#[derive(Debug, Clone)]
+pub struct OffsetTimeProvider<T: TimeProvider> {
+    pub inner: T,
+    pub offset: i64,
+}
+
+impl<T: TimeProvider> OffsetTimeProvider<T> {
+    pub fn new(inner: T, offset: i64) -> Self {
+        OffsetTimeProvider { inner, offset }
+    }
+}
+
+impl<T: TimeProvider> TimeProvider for OffsetTimeProvider<T> {
+    fn get_time(&self) -> u64 {
+        self.inner.get_time().saturating_add_signed(self.offset)
+    }
+}
+
+/// Marks that no frame is running, so [`FrameTimeProvider`] asks the inner provider
+const NO_FRAME: u64 = u64::MAX;
+
+/// Lets every update in a loop iteration share one timestamp instead of each asking the clock.
+///
+/// While a [`FrameGuard`] from [`FrameTimeProvider::frame()`] is alive, this (and every clone of it,
+/// so every entry made with it) returns the time the frame started. Otherwise it's just the inner
+/// provider. Frames don't nest, starting a new one replaces the old timestamp.
+#[derive(Debug, Clone)]
+pub struct FrameTimeProvider<T: TimeProvider> {
+    inner: T,
+    frame: Arc<AtomicU64>,
+}
+
+impl<T: TimeProvider> FrameTimeProvider<T> {
+    pub fn new(inner: T) -> Self {
+        FrameTimeProvider {
+            inner,
+            frame: Arc::new(AtomicU64::new(NO_FRAME)),
+        }
+    }
+
+    /// Reads the inner provider once and uses that time until the guard is dropped.
+    #[must_use = "the frame ends as soon as the guard is dropped"]
+    pub fn frame(&self) -> FrameGuard<'_, T> {
+        let timestamp = self.inner.get_time();
+        self.frame.store(timestamp, Ordering::Relaxed);
+
+        FrameGuard {
+            provider: self,
+            timestamp,
+        }
+    }
+}
+
+impl<T: TimeProvider> TimeProvider for FrameTimeProvider<T> {
+    fn get_time(&self) -> u64 {
+        match self.frame.load(Ordering::Relaxed) {
+            NO_FRAME => self.inner.get_time(),
+            timestamp => timestamp,
+        }
+    }
+}
+
+/// Keeps a [`FrameTimeProvider`]'s time fixed, see [`FrameTimeProvider::frame()`].
+pub struct FrameGuard<'a, T: TimeProvider> {
+    provider: &'a FrameTimeProvider<T>,
+    timestamp: u64,
+}
+
+impl<T: TimeProvider> FrameGuard<'_, T> {
+    /// The timestamp everything in this frame is logged with.
+    #[must_use]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl<T: TimeProvider> Drop for FrameGuard<'_, T> {
+    fn drop(&mut self) {
+        self.provider.frame.store(NO_FRAME, Ordering::Relaxed);
+    }
+}
+
+enum RecvState {
+    Msg(Box<[u8]>),
+    // Control records are split out so the worker knows which entries are open
+    Start(u32, Box<[u8]>),
+    SetMetadata(u32, Box<[u8]>),
+    Finish(u32, Box<[u8]>),
+    /// Holds a `(W, Sender<Result<W>>)`, it's type erased so entries don't need to know about `W`
+    Rotate(Box<dyn Any + Send>),
+    /// A data record in a buffer from the [`BufferPool`], which gets it back once written
+    Pooled(Vec<u8>),
+    /// Write the checksum trailer with this timestamp, then stop
+    Checksum(u64),
+    Stop,
+}
+
+type RotateReply<W> = Sender<Result<W, WriterError>>;
+
+impl RecvState {
+    /// Data records are the only thing a [`Ring`] is allowed to drop.
+    fn is_data(&self) -> bool {
+        matches!(self, RecvState::Msg(_) | RecvState::Pooled(_))
+    }
+}
+
+/// Where records are queued for the worker.
+#[derive(Clone)]
+enum Queue {
+    Channel(Sender<RecvState>),
+    Ring(RingSender),
+}
+
+impl Queue {
+    fn send(&self, msg: RecvState) -> Result<(), WriterError> {
+        match self {
+            Queue::Channel(sender) => sender.send(msg).map_err(|_| WriterError::Closed),
+            Queue::Ring(ring) => ring.0.push(msg),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Queue::Channel(sender) => sender.len(),
+            Queue::Ring(ring) => lock(&ring.0.queue).len(),
+        }
+    }
+}
+
+/// The worker's end of a [`Queue`].
+enum QueueReceiver {
+    Channel(Receiver<RecvState>),
+    Ring(Arc<Ring>),
+}
+
+/// What [`QueueReceiver::recv_until()`] got.
+enum Received {
+    Msg(RecvState),
+    TimedOut,
+    /// Nothing can be sent anymore
+    Closed,
+}
+
+impl QueueReceiver {
+    /// Waits for the next message, but only until `deadline` if there is one.
+    fn recv_until(&self, deadline: Option<Instant>) -> Received {
+        match self {
+            QueueReceiver::Channel(recv) => {
+                let res = match deadline {
+                    Some(deadline) => recv
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                        .map_err(|err| err == ReceiveErrorTimeout::Timeout),
+                    None => recv.recv().map_err(|_| false),
+                };
+
+                match res {
+                    Ok(msg) => Received::Msg(msg),
+                    Err(true) => Received::TimedOut,
+                    Err(false) => Received::Closed,
+                }
+            }
+            QueueReceiver::Ring(ring) => ring.pop_until(deadline),
+        }
+    }
+}
+
+impl Drop for QueueReceiver {
+    fn drop(&mut self) {
+        if let QueueReceiver::Ring(ring) = self {
+            ring.closed.store(true, Ordering::Release);
+            lock(&ring.queue).clear();
+        }
+    }
+}
+
+/// A queue that drops the oldest data record instead of growing past its capacity, see
+/// [`WPILOGWriterBuilder::ring()`].
+struct Ring {
+    queue: Mutex<VecDeque<RecvState>>,
+    ready: Condvar,
+    capacity: usize,
+    dropped: AtomicU64,
+    /// How many [`RingSender`]s are left, the worker stops waiting once there are none
+    senders: AtomicUsize,
+    /// Set once the worker stops, like a closed channel
+    closed: AtomicBool,
+}
+
+impl Ring {
+    fn push(&self, msg: RecvState) -> Result<(), WriterError> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(WriterError::Closed);
+        }
+
+        let mut queue = lock(&self.queue);
+
+        // Control records are never dropped, so a queue full of them is allowed to grow
+        if msg.is_data() && queue.len() >= self.capacity {
+            if let Some(oldest) = queue.iter().position(RecvState::is_data) {
+                queue.remove(oldest);
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        queue.push_back(msg);
+        drop(queue);
+        self.ready.notify_one();
+
+        Ok(())
+    }
+
+    fn pop_until(&self, deadline: Option<Instant>) -> Received {
+        let mut queue = lock(&self.queue);
+
+        loop {
+            if let Some(msg) = queue.pop_front() {
+                return Received::Msg(msg);
+            }
+
+            if self.senders.load(Ordering::Acquire) == 0 {
+                return Received::Closed;
+            }
+
+            queue = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if timeout.is_zero() {
+                        return Received::TimedOut;
+                    }
+
+                    self.ready
+                        .wait_timeout(queue, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .ready
+                    .wait(queue)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+/// Keeps count of the [`Ring`]'s senders, since unlike a channel it can't tell by itself.
+struct RingSender(Arc<Ring>);
+
+impl RingSender {
+    fn new(ring: Arc<Ring>) -> RingSender {
+        ring.senders.fetch_add(1, Ordering::AcqRel);
+        RingSender(ring)
+    }
+}
+
+impl Clone for RingSender {
+    fn clone(&self) -> Self {
+        RingSender::new(Arc::clone(&self.0))
+    }
+}
+
+impl Drop for RingSender {
+    fn drop(&mut self) {
+        if self.0.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Taking the lock makes sure the worker is either waiting or will see there are no senders
+            let _queue = lock(&self.0.queue);
+            self.0.ready.notify_all();
+        }
+    }
+}
+
+fn write_header<W: Write>(
+    writer: &mut W,
+    format: HeaderFormat,
+    extra_header: &[u8],
+) -> std::io::Result<()> {
+    // The builder already makes sure this fits
+    #[allow(clippy::cast_possible_truncation)]
+    let length = extra_header.len() as u32;
+
+    writer.write_all(&format.magic)?;
+    writer.write_all(&le::encode_u16(format.version))?;
+    writer.write_all(&le::encode_u32(length))?;
+    writer.write_all(extra_header)
+}
+
+/// Reusable record buffers, handed back by the worker after writing them.
+struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    /// How many buffers to keep around at most, extra ones are dropped
+    size: usize,
+}
+
+impl BufferPool {
+    fn take(&self) -> Vec<u8> {
+        lock(&self.buffers).pop().unwrap_or_default()
+    }
+
+    fn give_back(&self, mut buf: Vec<u8>) {
+        let mut buffers = lock(&self.buffers);
+        if buffers.len() < self.size {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+}
+
+/// The encoded `Start` and latest `SetMetadata` record of an entry, to re-emit after rotating
+struct OpenEntry {
+    start: Box<[u8]>,
+    metadata: Option<Box<[u8]>>,
+}
+
+/// A data record held back by the [`Reorder`] buffer.
+struct Held {
+    timestamp: u64,
+    /// Breaks ties so records with the same timestamp keep the order they were logged in
+    seq: u64,
+    msg: RecvState,
+}
+
+impl PartialEq for Held {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Held {}
+
+impl PartialOrd for Held {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Held {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (self.timestamp, self.seq).cmp(&(other.timestamp, other.seq))
+    }
+}
+
+/// Data records waiting to be written in timestamp order, see [`WPILOGWriterBuilder::reorder_window()`].
+struct Reorder {
+    /// In microseconds, like timestamps
+    window: u64,
+    held: BinaryHeap<Reverse<Held>>,
+    /// The latest timestamp received so far
+    newest: u64,
+    seq: u64,
+}
+
+impl Reorder {
+    fn push(&mut self, msg: RecvState) {
+        let data = match &msg {
+            RecvState::Msg(data) => &data[..],
+            RecvState::Pooled(data) => &data[..],
+            _ => unreachable!("Only data records are reordered"),
+        };
+        // Records are encoded by this crate, so they always parse
+        let timestamp = parse_record_ref(data).map_or(self.newest, |(record, _)| record.timestamp);
+
+        self.newest = self.newest.max(timestamp);
+        self.seq += 1;
+        self.held.push(Reverse(Held {
+            timestamp,
+            seq: self.seq,
+            msg,
+        }));
+    }
+
+    /// The oldest held record, if it's now more than the window older than the newest one.
+    fn pop_ready(&mut self) -> Option<RecvState> {
+        let Reverse(oldest) = self.held.peek()?;
+        if oldest.timestamp.saturating_add(self.window) > self.newest {
+            return None;
+        }
+
+        self.held.pop().map(|Reverse(held)| held.msg)
+    }
+
+    fn pop(&mut self) -> Option<RecvState> {
+        self.held.pop().map(|Reverse(held)| held.msg)
+    }
+}
+
+/// The state of the worker thread, which writes everything it receives until told to stop.
+struct Worker<W: Write + Send + 'static> {
+    writer: W,
+    /// Rewritten at the start of every rotated file, along with the extra header
+    header: HeaderFormat,
+    extra_header: Box<[u8]>,
+    open: BTreeMap<u32, OpenEntry>,
+    capture_errors: bool,
+    /// The first error seen while `capture_errors` is set
+    captured_error: Option<std::io::Error>,
+    /// Every record byte written since the file header, if checksumming
+    crc: Option<Crc32>,
+    pool: Option<Arc<BufferPool>>,
+    flush_every_records: Option<u64>,
+    flush_interval: Option<Duration>,
+    /// Records written since the last flush
+    unflushed: u64,
+    last_flush: Instant,
+    reorder: Option<Reorder>,
+}
+
+impl<W: Write + Send + 'static> Worker<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self.writer.write_all(data) {
+            Err(err) if self.capture_errors => {
+                self.captured_error.get_or_insert(err);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Writes (part of) a record, as opposed to the file header.
+    fn write_record(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if let Some(crc) = &mut self.crc {
+            crc.update(data);
+        }
+
+        self.write(data)?;
+
+        self.unflushed += 1;
+        let too_many = self
+            .flush_every_records
+            .is_some_and(|records| self.unflushed >= records);
+        let too_long = self
+            .flush_interval
+            .is_some_and(|interval| self.last_flush.elapsed() >= interval);
+
+        if too_many || too_long {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.unflushed = 0;
+        self.last_flush = Instant::now();
+
+        match self.writer.flush() {
+            Err(err) if self.capture_errors => {
+                self.captured_error.get_or_insert(err);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Writes a [`RecvState::Msg`] or [`RecvState::Pooled`] data record.
+    fn write_data(&mut self, msg: RecvState) -> std::io::Result<()> {
+        match msg {
+            RecvState::Msg(data) => self.write_record(&data),
+            RecvState::Pooled(data) => {
+                let res = self.write_record(&data);
+                if let Some(pool) = &self.pool {
+                    pool.give_back(data);
+                }
+                res
+            }
+            _ => unreachable!("Only data records are written with write_data"),
+        }
+    }
+
+    /// Queues a data record in the reorder buffer (if there is one) and writes whatever is ready.
+    fn hold_data(&mut self, msg: RecvState) -> std::io::Result<()> {
+        let Some(reorder) = &mut self.reorder else {
+            return self.write_data(msg);
+        };
+
+        reorder.push(msg);
+        while let Some(msg) = self.reorder.as_mut().and_then(Reorder::pop_ready) {
+            self.write_data(msg)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes everything in the reorder buffer, before anything that can't be reordered.
+    fn release_held(&mut self) -> std::io::Result<()> {
+        while let Some(msg) = self.reorder.as_mut().and_then(Reorder::pop) {
+            self.write_data(msg)?;
+        }
+
+        Ok(())
+    }
+
+    /// When the worker has to wake up to flush even if nothing else is logged.
+    fn flush_deadline(&self) -> Option<Instant> {
+        let interval = self.flush_interval?;
+        (self.unflushed > 0).then(|| self.last_flush + interval)
+    }
+
+    /// Writes the checksum entry with the CRC of everything before it.
+    fn write_checksum(&mut self, timestamp: u64) -> std::io::Result<()> {
+        let Some(crc) = self.crc else {
+            return Ok(());
+        };
+
+        let start = Record {
+            id: checksum::ENTRY_ID,
+            timestamp,
+            info: RecordInfo::Control(ControlData::Start {
+                name: checksum::ENTRY_NAME.into(),
+                r#type: checksum::ENTRY_TYPE.into(),
+                metadata: "".into(),
+            }),
+        };
+        let value = Record {
+            id: checksum::ENTRY_ID,
+            timestamp,
+            info: RecordInfo::Data(le::encode_i64(crc.finish().into()).into()),
+        };
+
+        self.write(&start.encode())?;
+        self.write(&value.encode())
+    }
+
+    // Taking the receiver matters, dropping it when the worker stops is what closes the queue
+    #[allow(clippy::needless_pass_by_value)]
+    fn run(mut self, recv: QueueReceiver) -> std::io::Result<W> {
+        loop {
+            let item = match recv.recv_until(self.flush_deadline()) {
+                Received::Msg(item) => item,
+                Received::TimedOut => {
+                    self.flush()?;
+                    continue;
+                }
+                Received::Closed => {
+                    self.release_held()?;
+                    break;
+                }
+            };
+
+            if item.is_data() {
+                self.hold_data(item)?;
+                continue;
+            }
+            self.release_held()?;
+
+            match item {
+                RecvState::Msg(_) | RecvState::Pooled(_) => {
+                    unreachable!("Data records are held first")
+                }
+                RecvState::Start(id, data) => {
+                    self.write_record(&data)?;
+                    self.open.insert(
+                        id,
+                        OpenEntry {
+                            start: data,
+                            metadata: None,
+                        },
+                    );
+                }
+                RecvState::SetMetadata(id, data) => {
+                    self.write_record(&data)?;
+                    if let Some(entry) = self.open.get_mut(&id) {
+                        entry.metadata = Some(data);
+                    }
+                }
+                RecvState::Finish(id, data) => {
+                    self.write_record(&data)?;
+                    self.open.remove(&id);
+                }
+                RecvState::Rotate(rotate) => {
+                    let Ok(rotate) = rotate.downcast::<(W, RotateReply<W>)>() else {
+                        unreachable!("Rotate is only ever sent with the writer's own type")
+                    };
+                    let (new_writer, reply) = *rotate;
+
+                    let mut old_writer = std::mem::replace(&mut self.writer, new_writer);
+                    let res = old_writer
+                        .flush()
+                        .map(|()| old_writer)
+                        .map_err(WriterError::Io);
+
+                    // If nobody is waiting for the old writer anymore it can just be dropped
+                    let _ = reply.send(res);
+
+                    let mut header = vec![];
+                    write_header(&mut header, self.header, &self.extra_header)?;
+                    self.write(&header)?;
+
+                    let mut open = vec![];
+                    for entry in self.open.values() {
+                        open.extend_from_slice(&entry.start);
+                        if let Some(metadata) = &entry.metadata {
+                            open.extend_from_slice(metadata);
+                        }
+                    }
+
+                    // The checksum only covers the current file
+                    if self.crc.is_some() {
+                        self.crc = Some(Crc32::new());
+                    }
+                    self.write_record(&open)?;
+                }
+                RecvState::Checksum(timestamp) => {
+                    self.write_checksum(timestamp)?;
+                    break;
+                }
+                RecvState::Stop => {
+                    break;
+                }
+            }
+        }
+
+        let flushed = self.writer.flush();
+
+        match self.captured_error {
+            Some(err) => Err(err),
+            None => flushed.map(|()| self.writer),
+        }
+    }
+}
+
+/// Configures a [`WPILOGWriter`], [`WPILOGWriter::new()`] is the same as building with the defaults.
+#[derive(Debug, Clone)]
+// Every flag is an independent option, there's no state machine hiding in them
+#[allow(clippy::struct_excessive_bools)]
+pub struct WPILOGWriterBuilder {
+    channel_capacity: Option<usize>,
+    header: HeaderFormat,
+    extra_header: Box<[u8]>,
+    thread_name: String,
+    stack_size: Option<usize>,
+    on_worker_start: Option<WorkerHook>,
+    capture_errors: bool,
+    track_entries: bool,
+    checksum: bool,
+    buffer_pool: Option<usize>,
+    wall_clock_start: bool,
+    ring: Option<usize>,
+    flush_every_records: Option<u64>,
+    flush_interval: Option<Duration>,
+    reorder_window: Option<Duration>,
+    deterministic: bool,
+}
+
+/// Runs on the worker thread before it writes anything, see [`WPILOGWriterBuilder::on_worker_start()`].
+#[derive(Clone)]
+struct WorkerHook(Arc<dyn Fn() + Send + Sync>);
+
+impl std::fmt::Debug for WorkerHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WorkerHook")
+    }
+}
+
+impl Default for WPILOGWriterBuilder {
+    fn default() -> Self {
+        WPILOGWriterBuilder {
+            channel_capacity: None,
+            header: HeaderFormat::default(),
+            extra_header: Box::new([]),
+            thread_name: "wpilog-writer".into(),
+            stack_size: None,
+            on_worker_start: None,
+            capture_errors: false,
+            track_entries: false,
+            checksum: false,
+            buffer_pool: None,
+            wall_clock_start: false,
+            ring: None,
+            flush_every_records: None,
+            flush_interval: None,
+            reorder_window: None,
+            deterministic: false,
+        }
+    }
+}
+
+impl WPILOGWriterBuilder {
+    #[must_use]
+    pub fn new() -> WPILOGWriterBuilder {
+        WPILOGWriterBuilder::default()
+    }
+
+    /// Limits how many records can be waiting for the worker, logging blocks when it's full.
+    ///
+    /// By default there is no limit.
+    #[must_use]
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the free-form extra header at the start of the log, empty by default.
+    #[must_use]
+    pub fn extra_header(mut self, extra_header: impl Into<Vec<u8>>) -> Self {
+        self.extra_header = extra_header.into().into_boxed_slice();
+        self
+    }
+
+    /// Sets the name of the worker thread, `wpilog-writer` by default.
+    #[must_use]
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = name.into();
+        self
+    }
+
+    /// Sets the worker thread's stack size in bytes, instead of the platform default.
+    ///
+    /// The worker only needs a small stack, so this can save memory on constrained devices.
+    #[must_use]
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Calls `hook` on the worker thread as soon as it starts.
+    ///
+    /// This is where to lower the worker's priority below the control loop, like with `nice`
+    /// or `setpriority` on Linux, since there's no portable way to do that. It's called once for
+    /// every writer built with this builder.
+    #[must_use]
+    pub fn on_worker_start(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_worker_start = Some(WorkerHook(Arc::new(hook)));
+        self
+    }
+
+    /// If enabled, the worker keeps going when the underlying writer fails instead of stopping,
+    /// and [`WPILOGWriter::join()`] returns the first error as [`WriterError::Io`].
+    ///
+    /// Records that failed to write are lost either way, but this way logging never starts
+    /// returning [`WriterError::Closed`] in the middle of a run.
+    #[must_use]
+    pub fn capture_errors(mut self, capture_errors: bool) -> Self {
+        self.capture_errors = capture_errors;
+        self
+    }
+
+    /// Keeps track of which entries are still open, which is needed for [`WPILOGWriter::finish_all()`].
+    #[must_use]
+    pub fn track_entries(mut self, track_entries: bool) -> Self {
+        self.track_entries = track_entries;
+        self
+    }
+
+    /// Appends a CRC32 of every record to the log when it's joined, see [`WPILOGReader::verify_checksum()`].
+    ///
+    /// This isn't part of the standard format, the checksum is written as a normal `int64` entry
+    /// named [`checksum::ENTRY_NAME`] (with the reserved id 1) so other readers just ignore it.
+    /// After a rotation the checksum only covers the new file.
+    ///
+    /// [`WPILOGReader::verify_checksum()`]: crate::reader::WPILOGReader::verify_checksum
+    #[must_use]
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Encodes data records into a pool of up to `size` reused buffers instead of allocating for each one.
+    ///
+    /// The worker hands every buffer back to the pool once it's written, so this only helps if logging
+    /// doesn't get far ahead of the worker. Control records are still allocated.
+    #[must_use]
+    pub fn buffer_pool(mut self, size: usize) -> Self {
+        self.buffer_pool = Some(size);
+        self
+    }
+
+    /// Never blocks logging on the worker: once `capacity` records are waiting, logging another data
+    /// record drops the oldest waiting data record instead. This takes priority over
+    /// [`WPILOGWriterBuilder::channel_capacity()`].
+    ///
+    /// **Dropped records are lost for good**, and nothing in the log says they're missing, only
+    /// [`WriterStats::dropped`] does. Control records are never dropped, so entries always open and
+    /// close properly, but the queue can grow past `capacity` if it's filled with them.
+    #[must_use]
+    pub fn ring(mut self, capacity: usize) -> Self {
+        self.ring = Some(capacity);
+        self
+    }
+
+    /// Flushes the underlying writer after every `records` records, so a crash loses at most that
+    /// many. By default it's only flushed on rotation and when the writer is joined.
+    ///
+    /// Can be combined with [`WPILOGWriterBuilder::flush_interval()`], whichever comes first flushes.
+    #[must_use]
+    pub fn flush_every_records(mut self, records: u64) -> Self {
+        self.flush_every_records = Some(records);
+        self
+    }
+
+    /// Flushes the underlying writer once anything written has waited `interval`, even if nothing
+    /// else is logged, so a crash loses at most that much time of records.
+    #[must_use]
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Holds data records back so they're written in timestamp order, even if entries logged with
+    /// their own timestamps reach the worker slightly out of order.
+    ///
+    /// A record is written once one at least `window` newer (in log time) has been logged, so records
+    /// are sorted as long as none arrives more than `window` late. Bigger windows fix worse
+    /// misordering, but everything reaches the file (and any crash recovery) that much later, and
+    /// that much of the log is held in memory. Whatever is still held is written when the writer is
+    /// joined or rotated.
+    ///
+    /// Control records can't be moved, a `Start` has to stay before the entry's data, so every control
+    /// record first writes everything held (in order) and records are only sorted between them.
+    #[must_use]
+    pub fn reorder_window(mut self, window: Duration) -> Self {
+        self.reorder_window = Some(window);
+        self
+    }
+
+    /// Makes the log bytes the same every time for the same sequence of calls, for golden file tests.
+    ///
+    /// Entry ids are always handed out in the order entries are made, so what can change between
+    /// runs is which thread gets there first. With this set, making an entry on any other thread
+    /// than the one that built the writer returns [`WriterError::WrongThread`]. Building fails if
+    /// [`WPILOGWriterBuilder::wall_clock_start()`] or [`WPILOGWriterBuilder::ring()`] is also set,
+    /// since the wall clock and what the ring drops are different every run.
+    ///
+    /// The timestamps still come from the [`TimeProvider`], so it has to be deterministic too, and
+    /// records logged to entries from other threads are written in whatever order they arrive.
+    #[must_use]
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Sets the extra header to `metadata` as JSON, for session context like the event name, match
+    /// number and alliance. Read it back with [`WPILOGReader::session_metadata()`].
+    ///
+    /// This replaces any [`WPILOGWriterBuilder::extra_header()`]. The JSON is always a single line, so
+    /// [`WPILOGWriterBuilder::wall_clock_start()`] can still add its own line after it.
+    ///
+    /// [`WPILOGReader::session_metadata()`]: crate::reader::WPILOGReader::session_metadata
+    #[cfg(feature = "serde_json")]
+    #[must_use]
+    // Taken by value like the other options, so a `json!` literal can be passed straight in
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn session_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.extra_header = metadata.to_string().into_bytes().into_boxed_slice();
+        self
+    }
+
+    /// Records what the wall clock time was when the log started in the extra header, so readers can
+    /// convert timestamps to absolute times with [`WPILOGReader::wall_clock_start()`].
+    ///
+    /// This is a line `wall_clock_start={unix microseconds}@{log timestamp}`, added after any other extra
+    /// header (with a newline in between). It's only a convention of this crate, other tools just see
+    /// it as part of the free-form extra header.
+    ///
+    /// [`WPILOGReader::wall_clock_start()`]: crate::reader::WPILOGReader::wall_clock_start
+    #[must_use]
+    pub fn wall_clock_start(mut self, wall_clock_start: bool) -> Self {
+        self.wall_clock_start = wall_clock_start;
+        self
+    }
+
+    /// Writes a different magic and version instead of the standard `WPILOG` version 1.0, for vendor
+    /// variants of the format. Other tools won't be able to read the log.
+    #[must_use]
+    pub fn header(mut self, header: HeaderFormat) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Makes the queue records are sent to the worker through.
+    fn queue(&self) -> (Queue, QueueReceiver) {
+        match (self.ring, self.channel_capacity) {
+            (Some(capacity), _) => {
+                let ring = Arc::new(Ring {
+                    queue: Mutex::new(VecDeque::with_capacity(capacity)),
+                    ready: Condvar::new(),
+                    capacity,
+                    dropped: AtomicU64::new(0),
+                    senders: AtomicUsize::new(0),
+                    closed: AtomicBool::new(false),
+                });
+
+                (
+                    Queue::Ring(RingSender::new(Arc::clone(&ring))),
+                    QueueReceiver::Ring(ring),
+                )
+            }
+            (None, Some(capacity)) => {
+                let (sender, recv) = kanal::bounded(capacity);
+                (Queue::Channel(sender), QueueReceiver::Channel(recv))
+            }
+            (None, None) => {
+                let (sender, recv) = kanal::unbounded();
+                (Queue::Channel(sender), QueueReceiver::Channel(recv))
+            }
+        }
+    }
+
+    /// Writes the header and starts the worker thread.
+    ///
+    /// # Errors
+    /// If the extra header is too long, writing the header fails, the worker thread can't be spawned,
+    /// or [`WPILOGWriterBuilder::deterministic()`] is combined with an option that isn't.
+    pub fn build<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static>(
+        mut self,
+        mut writer: W,
+        time_provider: T,
+    ) -> Result<WPILOGWriter<T, W>> {
+        if self.deterministic && (self.wall_clock_start || self.ring.is_some()) {
+            return Err(WpilogError::InvalidInput(
+                "A deterministic writer can't record the wall clock or drop records".into(),
+            ));
+        }
+
+        if self.wall_clock_start {
+            // Microseconds since 1970 won't overflow a u64 for a few hundred thousand years
+            #[allow(clippy::cast_possible_truncation)]
+            let unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| WpilogError::InvalidInput("The system clock is before 1970".into()))?
+                .as_micros() as u64;
+
+            let mut extra_header = self.extra_header.into_vec();
+            if !extra_header.is_empty() {
+                extra_header.push(b'\n');
+            }
+            extra_header.extend_from_slice(
+                format!("{WALL_CLOCK_PREFIX}{unix}@{}", time_provider.get_time()).as_bytes(),
+            );
+            self.extra_header = extra_header.into_boxed_slice();
+        }
+
+        if u32::try_from(self.extra_header.len()).is_err() {
+            return Err(WpilogError::InvalidInput(format!(
+                "Extra header can be at max {} bytes long",
+                u32::MAX
+            )));
+        }
+
+        let (sender, recv) = self.queue();
+
+        write_header(&mut writer, self.header, &self.extra_header)?;
+
+        let pool = self.buffer_pool.map(|size| {
+            Arc::new(BufferPool {
+                buffers: Mutex::new(Vec::with_capacity(size)),
+                size,
+            })
+        });
+
+        let worker = Worker {
+            writer,
+            header: self.header,
+            extra_header: self.extra_header,
+            open: BTreeMap::new(),
+            capture_errors: self.capture_errors,
+            captured_error: None,
+            crc: self.checksum.then(Crc32::new),
+            pool: pool.clone(),
+            flush_every_records: self.flush_every_records,
+            flush_interval: self.flush_interval,
+            unflushed: 0,
+            last_flush: Instant::now(),
+            reorder: self.reorder_window.map(|window| Reorder {
+                // A window of over half a million years is as good as infinite
+                window: u64::try_from(window.as_micros()).unwrap_or(u64::MAX),
+                held: BinaryHeap::new(),
+                newest: 0,
+                seq: 0,
+            }),
+        };
+
+        let mut thread = std::thread::Builder::new().name(self.thread_name);
+        if let Some(size) = self.stack_size {
+            thread = thread.stack_size(size);
+        }
+
+        let on_start = self.on_worker_start;
+        let handle = thread.spawn(move || {
+            if let Some(WorkerHook(hook)) = on_start {
+                hook();
+            }
+
+            worker.run(recv)
+        })?;
+
+        Ok(WPILOGWriter {
+            id: AtomicU32::new(if self.checksum {
+                checksum::ENTRY_ID + 1
+            } else {
+                1
+            }),
+            queue: sender,
+            handle,
+            time_provider,
+            open_entries: self.track_entries.then(OpenEntries::default),
+            checksum: self.checksum,
+            schemas: Mutex::default(),
+            pool,
+            owner: self.deterministic.then(|| std::thread::current().id()),
+        })
     }
 }
 
-pub trait TimeProvider {
-    fn get_time(&self) -> u64;
+/// See [`WPILOGWriter::stats()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriterStats {
+    /// How many data records were dropped because the [ring](WPILOGWriterBuilder::ring()) was full
+    pub dropped: u64,
+    /// The [`WPILOGWriter::queue_len()`] when the stats were taken
+    pub queued: usize,
 }
 
-enum RecvState {
-    Msg(Box<[u8]>),
-    Stop,
+/// A `Vec<u8>` that can be written to from the worker while still being reachable from outside,
+/// see [`WPILOGWriter::new_in_memory()`].
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuffer(pub Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        lock(&self.0).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
+/// Writes a log on a background worker thread, entries queue their records for it to write.
+///
+/// # Ordering
+/// Records are written in the order they were queued, and the queue is shared by every entry, so
+/// anything one thread logs is in the file in the order it was logged. Across threads the order is
+/// whichever queued first. An entry's `Start` record is queued before [`LogWriter::make_entry()`]
+/// returns, so it always comes before the entry's first data record, whichever thread that's
+/// logged from (handing the entry to another thread already makes everything before that visible
+/// to it). Control records are never dropped, even by a [`WPILOGWriterBuilder::ring()`] queue.
 pub struct WPILOGWriter<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static> {
     id: AtomicU32,
-    channel: Sender<RecvState>,
-    handle: JoinHandle<W>,
+    queue: Queue,
+    handle: JoinHandle<std::io::Result<W>>,
     time_provider: T,
+    open_entries: Option<OpenEntries>,
+    checksum: bool,
+    /// Every registered schema by name, the entries are kept so they're never finished
+    schemas: Mutex<HashMap<String, RawEntry<T>>>,
+    pool: Option<Arc<BufferPool>>,
+    /// The only thread allowed to make entries, if deterministic
+    owner: Option<ThreadId>,
 }
 
+/// Every entry that hasn't been finished yet, with the flag its [`RawEntry`] checks before logging.
+type OpenEntries = Arc<Mutex<BTreeMap<u32, Arc<RwLock<bool>>>>>;
+
 impl<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static> WPILOGWriter<T, W> {
+    /// Makes the `/.schema/{name}` entry and logs the first version of the schema to it, the caller
+    /// holds the `schemas` lock.
+    fn log_new_schema(
+        &self,
+        schemas: &mut HashMap<String, RawEntry<T>>,
+        name: &str,
+        type_str: &str,
+        schema: &[u8],
+    ) -> Result<()> {
+        let entry = self.make_entry(
+            format!("/.schema/{name}"),
+            type_str.to_string(),
+            String::new(),
+        )?;
+        entry.log_data(schema.into())?;
+        schemas.insert(name.to_string(), entry);
+
+        Ok(())
+    }
+
+    /// Shortcut for building with the default [`WPILOGWriterBuilder`].
+    ///
     /// # Panics
     ///
-    /// Can panic is writer fails `write_all()`
-    pub fn new(mut writer: W, time_provider: T) -> WPILOGWriter<T, W> {
-        let (sender, recv) = kanal::unbounded();
+    /// Can panic is writer fails `write_all()` on the header, or the worker thread can't be spawned
+    pub fn new(writer: W, time_provider: T) -> WPILOGWriter<T, W> {
+        WPILOGWriterBuilder::new()
+            .build(writer, time_provider)
+            .expect("Failed to start the wpilog writer")
+    }
 
-        writer.write_all(HEADER_STRING).unwrap();
-        writer.write_all(&HEADER_VERSION.to_le_bytes()).unwrap();
-        writer.write_all(&[0, 0, 0, 0]).unwrap();
+    /// Shortcut for building with [`WPILOGWriterBuilder::ring()`] and otherwise the defaults.
+    ///
+    /// # Panics
+    ///
+    /// Can panic is writer fails `write_all()` on the header, or the worker thread can't be spawned
+    pub fn new_ring(writer: W, time_provider: T, capacity: usize) -> WPILOGWriter<T, W> {
+        WPILOGWriterBuilder::new()
+            .ring(capacity)
+            .build(writer, time_provider)
+            .expect("Failed to start the wpilog writer")
+    }
 
-        let handle = std::thread::spawn(move || {
-            for item in recv {
-                match item {
-                    RecvState::Msg(data) => {
-                        writer.write_all(&data).unwrap();
-                    }
-                    RecvState::Stop => {
-                        break;
-                    }
-                }
+    /// Counters about what the writer has done so far.
+    #[must_use]
+    pub fn stats(&self) -> WriterStats {
+        WriterStats {
+            dropped: match &self.queue {
+                Queue::Ring(ring) => ring.0.dropped.load(Ordering::Relaxed),
+                Queue::Channel(_) => 0,
+            },
+            queued: self.queue_len(),
+        }
+    }
+
+    /// How many records are waiting for the worker right now.
+    ///
+    /// Logging this every loop shows if the disk can't keep up, the backlog keeps growing instead of
+    /// staying near 0.
+    #[must_use]
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Shortcut for building with [`WPILOGWriterBuilder::buffer_pool()`] and otherwise the defaults.
+    ///
+    /// # Panics
+    ///
+    /// Can panic is writer fails `write_all()` on the header, or the worker thread can't be spawned
+    pub fn new_pooled(writer: W, time_provider: T, pool_size: usize) -> WPILOGWriter<T, W> {
+        WPILOGWriterBuilder::new()
+            .buffer_pool(pool_size)
+            .build(writer, time_provider)
+            .expect("Failed to start the wpilog writer")
+    }
+
+    /// Switches to writing to `new_sink`, returning the old sink once everything before this has been written to it.
+    ///
+    /// The new sink gets a fresh header, followed by the `Start` (and latest `SetMetadata`) records of every
+    /// entry that hasn't finished yet, so it can be read on its own. Entries keep working as before.
+    ///
+    /// This blocks until the worker has caught up to the rotation.
+    ///
+    /// # Errors
+    /// Returns [`WriterError::Io`] if flushing the old sink failed, or [`WriterError::Closed`] if the
+    /// worker has stopped (including failing to write the new header).
+    pub fn rotate(&self, new_sink: W) -> Result<W> {
+        let (reply, response): (RotateReply<W>, _) = kanal::bounded(1);
+
+        self.queue
+            .send(RecvState::Rotate(Box::new((new_sink, reply))))
+            .map_err(|_| WriterError::Closed)?;
+
+        Ok(response.recv().map_err(|_| WriterError::Closed)??)
+    }
+
+    /// Queues an already built [`Record`] as-is, for tools that produce records themselves like transcoders.
+    ///
+    /// The record's id isn't coordinated with entries made by this writer, so don't mix the two
+    /// unless the ids are known not to collide.
+    ///
+    /// # Errors
+    /// If it's a data record using the control id 0, or [`WriterError::Closed`] if the worker has stopped.
+    pub fn write_record(&self, record: &Record) -> Result<()> {
+        if record.id == 0 && matches!(record.info, RecordInfo::Data(_)) {
+            return Err(WpilogError::InvalidInput(
+                "Data records can't use entry id 0".into(),
+            ));
+        }
+
+        send_record(&self.queue, record)
+    }
+
+    /// Finishes every entry that is still open, in the order they were created.
+    ///
+    /// Logging to the entries afterwards returns [`WriterError::EntryFinished`], and dropping them
+    /// won't send a second `Finish` record.
+    ///
+    /// # Errors
+    /// If the writer wasn't built with [`WPILOGWriterBuilder::track_entries()`], or the worker has stopped.
+    pub fn finish_all(&self) -> Result<()> {
+        let Some(open_entries) = &self.open_entries else {
+            return Err(WpilogError::InvalidInput(
+                "Entry tracking is disabled for this writer".into(),
+            ));
+        };
+
+        let entries = std::mem::take(&mut *lock(open_entries));
+        let timestamp = self.time_provider.get_time();
+
+        for (id, finished) in entries {
+            // The entry may be finishing itself right now, whoever flips the flag sends the record.
+            // Holding the lock until it's sent keeps records logged meanwhile from landing after it.
+            let mut finished = write(&finished);
+            if *finished {
+                continue;
             }
+            *finished = true;
 
-            writer.flush().unwrap();
+            let record = Record {
+                id,
+                timestamp,
+                info: RecordInfo::Control(ControlData::Finish),
+            };
 
-            writer
-        });
+            send_record(&self.queue, &record)?;
+        }
 
-        WPILOGWriter {
-            id: AtomicU32::new(1),
-            channel: sender,
-            handle,
-            time_provider,
+        Ok(())
+    }
+
+    /// Instantly stops new messages from sending, and stops the worker after all previous messages have been written
+    ///
+    /// ANYTHING SENT AFTER THIS IS CALLED WILL NOT BE RECORDED, AND WILL BE LOST FOREVER!
+    ///
+    /// With [`WPILOGWriterBuilder::checksum()`] enabled, this is when the checksum trailer is written.
+    ///
+    /// # Errors
+    /// Returns [`WriterError::WorkerPanicked`] if the worker thread panicked, or [`WriterError::Io`]
+    /// if it stopped early because the underlying writer failed.
+    pub fn join(self) -> Result<W> {
+        let stop = if self.checksum {
+            RecvState::Checksum(self.time_provider.get_time())
+        } else {
+            RecvState::Stop
+        };
+
+        // Try best to gracefully stop channel, it'll forcefully stop when dropped anyways
+        let _ = self.queue.send(stop);
+
+        match self.handle.join() {
+            Err(panic) => {
+                let msg = if let Some(msg) = panic.downcast_ref::<&str>() {
+                    (*msg).to_string()
+                } else if let Some(msg) = panic.downcast_ref::<String>() {
+                    msg.clone()
+                } else {
+                    "<non-string panic payload>".to_string()
+                };
+
+                Err(WriterError::WorkerPanicked(msg).into())
+            }
+            Ok(Err(err)) => Err(WriterError::Io(err).into()),
+            Ok(Ok(val)) => Ok(val),
         }
     }
+}
 
-    /// Don't use this unless you know what you are doing.
+impl<T: TimeProvider + Clone + Send + Sync> WPILOGWriter<T, SharedBuffer> {
+    /// Shortcut for building with the defaults and writing to memory, handy for tests.
     ///
-    /// This method returns a [`RawEntry`], which requires you enforce that data is given the right format.
+    /// The returned buffer has the log in it once the writer is joined (and whatever has been written
+    /// so far before that).
     ///
-    /// Messing up data formatting can result in tools being unable to interpret what your logged values actually mean.
+    /// # Panics
     ///
+    /// Can panic if the worker thread can't be spawned
+    pub fn new_in_memory(time_provider: T) -> (Self, Arc<Mutex<Vec<u8>>>) {
+        let buffer = SharedBuffer::default();
+        let data = Arc::clone(&buffer.0);
+
+        (WPILOGWriter::new(buffer, time_provider), data)
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static> LogWriter<T>
+    for WPILOGWriter<T, W>
+{
     /// # Errors
-    /// This can error if the internal channel fails to send.
-    pub fn make_entry(
-        &self,
-        name: String,
-        r#type: String,
-        metadata: String,
-    ) -> Result<RawEntry<T>> {
-        let id = self.id.fetch_add(1, Ordering::Relaxed);
+    /// Returns [`WriterError::Closed`] if the writer's worker has stopped,
+    /// [`WriterError::TooManyEntries`] once all [`u32::MAX`] ids have been handed out, or
+    /// [`WriterError::WrongThread`] if it's deterministic and this isn't the thread that built it.
+    fn make_entry(&self, name: String, r#type: String, metadata: String) -> Result<RawEntry<T>> {
+        if self
+            .owner
+            .is_some_and(|owner| owner != std::thread::current().id())
+        {
+            return Err(WriterError::WrongThread.into());
+        }
+
+        // Id 0 is reserved for control records, so the counter wrapping to it means every id is used.
+        // Relaxed is enough, the update is atomic so ids are unique either way, and nothing else is
+        // published through the counter: the Start record is ordered by the queue instead.
+        let id = self
+            .id
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| {
+                (id != 0).then(|| id.wrapping_add(1))
+            })
+            .map_err(|_| WriterError::TooManyEntries)?;
         let record = Record {
             id,
             timestamp: self.time_provider.get_time(),
@@ -116,50 +1431,156 @@ impl<T: TimeProvider + Clone + Send + Sync, W: Write + Send + 'static> WPILOGWri
                 metadata: metadata.into_boxed_str(),
             }),
         };
-        self.channel.send(RecvState::Msg(record.encode()))?;
+        send_record(&self.queue, &record)?;
 
-        Ok(RawEntry {
+        let entry = RawEntry::new(
             id,
-            channel: self.channel.clone(),
-            time_provider: self.time_provider.clone(),
-        })
+            Some(self.queue.clone()),
+            self.time_provider.clone(),
+            self.open_entries.clone(),
+            self.pool.clone(),
+        );
+
+        if let Some(open_entries) = &self.open_entries {
+            lock(open_entries).insert(id, Arc::clone(&entry.inner.finished));
+        }
+
+        Ok(entry)
     }
 
-    /// Instantly stops new messages from sending, and stops the worker after all previous messages have been written
-    ///
-    /// ANYTHING SENT AFTER THIS IS CALLED WILL NOT BE RECORDED, AND WILL BE LOST FOREVER!
-    ///
-    /// # Errors
-    /// If the internal thread errors
-    pub fn join(self) -> Result<W> {
-        // Try best to gracefully stop channel, it'll forcefully stop when dropped anyways
-        let _ = self.channel.send(RecvState::Stop);
+    fn time_provider(&self) -> &T {
+        &self.time_provider
+    }
 
-        match self.handle.join() {
-            Err(err) => Err(format_err!("{err:#?}")),
-            Ok(val) => Ok(val),
+    /// Registering the same schema again logs the new version to the same entry.
+    fn register_schema(&self, name: &str, type_str: &str, schema: &[u8]) -> Result<()> {
+        // Holding the lock while logging makes sure nobody sees the schema as registered before it's queued
+        let mut schemas = lock(&self.schemas);
+
+        if let Some(entry) = schemas.get(name) {
+            entry.log_data(schema.into())?;
+            return Ok(());
+        }
+
+        self.log_new_schema(&mut schemas, name, type_str, schema)
+    }
+
+    /// Checked and logged under the same lock, so racing threads can't both log it.
+    fn register_schema_once(&self, name: &str, type_str: &str, schema: &[u8]) -> Result<bool> {
+        let mut schemas = lock(&self.schemas);
+
+        if schemas.contains_key(name) {
+            return Ok(false);
         }
+
+        self.log_new_schema(&mut schemas, name, type_str, schema)?;
+        Ok(true)
+    }
+
+    fn schema_registered(&self, name: &str) -> bool {
+        lock(&self.schemas).contains_key(name)
+    }
+}
+
+/// A writer that throws away everything logged to it, for turning logging off at runtime.
+///
+/// No thread is spawned and nothing is encoded or sent, so entries made from it are nearly free to update.
+#[derive(Debug, Clone)]
+pub struct NullWriter<T: TimeProvider + Clone + Send + Sync> {
+    time_provider: T,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> NullWriter<T> {
+    pub fn new(time_provider: T) -> NullWriter<T> {
+        NullWriter { time_provider }
+    }
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> LogWriter<T> for NullWriter<T> {
+    /// Makes an entry that silently discards anything logged to it.
+    fn make_entry(&self, _name: String, _type: String, _metadata: String) -> Result<RawEntry<T>> {
+        Ok(RawEntry::new(
+            0,
+            None,
+            self.time_provider.clone(),
+            None,
+            None,
+        ))
+    }
+
+    fn time_provider(&self) -> &T {
+        &self.time_provider
     }
 }
 
 /// A handle to write raw byte data to the log file. Usually a wrapper type is used.
+///
+/// Cloning is cheap and every clone logs to the same entry, the `Finish` record is only sent once the last clone is dropped
+/// (or [`RawEntry::finish()`] is called).
 pub struct RawEntry<T: TimeProvider + Clone + Send + Sync> {
+    inner: Arc<EntryHandle<T>>,
+}
+
+impl<T: TimeProvider + Clone + Send + Sync> Clone for RawEntry<T> {
+    fn clone(&self) -> Self {
+        RawEntry {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// The state shared by every clone of a [`RawEntry`], which finishes the entry when dropped.
+struct EntryHandle<T: TimeProvider + Clone + Send + Sync> {
     id: u32,
-    channel: Sender<RecvState>,
-    pub(super) time_provider: T,
+    /// [`None`] for entries from a [`NullWriter`]
+    queue: Option<Queue>,
+    time_provider: T,
+    open_entries: Option<OpenEntries>,
+    pool: Option<Arc<BufferPool>>,
+    /// Set once the `Finish` record has been sent, by [`RawEntry::finish()`] or
+    /// [`WPILOGWriter::finish_all()`]. Records are sent with it read locked, and the `Finish` with
+    /// it write locked, so nothing can be sent after the `Finish`.
+    finished: Arc<RwLock<bool>>,
 }
 
 impl Record {
+    /// How many bytes the [`Record`] takes up in the log, header included, without encoding it.
+    ///
+    /// This is worked out the same way the writer picks each field's width, so it's exact.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        let (id, payload) = match &self.info {
+            RecordInfo::Control(ControlData::Start {
+                name,
+                r#type,
+                metadata,
+            }) => (
+                0,
+                1 + 4 + 4 + name.len() + 4 + r#type.len() + 4 + metadata.len(),
+            ),
+            RecordInfo::Control(ControlData::Finish) => (0, 1 + 4),
+            RecordInfo::Control(ControlData::SetMetadata(metadata)) => {
+                (0, 1 + 4 + 4 + metadata.len())
+            }
+            RecordInfo::Data(data) => (self.id, data.len()),
+        };
+
+        1 + int_len(id.into()) + int_len(payload as u64) + int_len(self.timestamp) + payload
+    }
+
     /// Turn the [`Record`] into it's binary representation.
-    fn encode(&self) -> Box<[u8]> {
-        // TODO: Figure out slice size first
-        // This should be possible but might not be that trivial...
+    pub(crate) fn encode(&self) -> Box<[u8]> {
+        let mut buf = vec![];
+        self.encode_into(&mut buf);
 
-        let timestamp_data = encode_int(self.timestamp);
+        buf.into_boxed_slice()
+    }
 
+    /// Appends the [`Record`]'s binary representation to `buf`.
+    pub(crate) fn encode_into(&self, buf: &mut Vec<u8>) {
         match &self.info {
             RecordInfo::Control(ctrl) => {
-                let mut ret = vec![];
+                let timestamp_data = encode_int(self.timestamp);
 
                 let mut data = match ctrl {
                     ControlData::Start {
@@ -168,35 +1589,35 @@ impl Record {
                         metadata,
                     } => {
                         let mut data = vec![0];
-                        data.extend_from_slice(&self.id.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(self.id));
 
                         let len: u32 = name.len().try_into().expect("TODO: deal with this");
-                        data.extend_from_slice(&len.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(len));
 
                         data.extend_from_slice(name.as_bytes());
 
                         let len: u32 = r#type.len().try_into().expect("TODO: deal with this");
-                        data.extend_from_slice(&len.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(len));
 
                         data.extend_from_slice(r#type.as_bytes());
 
                         let len: u32 = metadata.len().try_into().expect("TODO: deal with this");
-                        data.extend_from_slice(&len.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(len));
 
                         data.extend_from_slice(metadata.as_bytes());
 
                         data
                     }
                     ControlData::Finish => {
-                        let encoded = &self.id.to_le_bytes();
+                        let encoded = &le::encode_u32(self.id);
                         vec![1, encoded[0], encoded[1], encoded[2], encoded[3]]
                     }
                     ControlData::SetMetadata(metadata) => {
                         let mut data = vec![2];
-                        data.extend_from_slice(&self.id.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(self.id));
 
                         let len: u32 = metadata.len().try_into().expect("TODO: deal with this");
-                        data.extend_from_slice(&len.to_le_bytes());
+                        data.extend_from_slice(&le::encode_u32(len));
 
                         data.extend_from_slice(metadata.as_bytes());
 
@@ -215,118 +1636,235 @@ impl Record {
                     bitfield |= (((timestamp_data.len() - 1) & 0x7) as u8) << 4;
                 }
 
-                ret.push(bitfield);
+                buf.reserve_exact(2 + size_data.len() + timestamp_data.len() + data.len());
+                buf.push(bitfield);
 
-                ret.extend_from_slice(&[0]);
-                ret.extend_from_slice(&size_data);
-                ret.extend_from_slice(&timestamp_data);
+                buf.push(0);
+                buf.extend_from_slice(&size_data);
+                buf.extend_from_slice(&timestamp_data);
 
-                ret.append(&mut data);
-
-                ret.into_boxed_slice()
+                buf.append(&mut data);
             }
-            RecordInfo::Data(data) => {
-                debug_assert_ne!(
-                    self.id, 0,
-                    "Data records can't have ID 0 or stuff will go wrong"
-                );
-
-                let id_data = encode_int(self.id.into());
-                let size_data = encode_int(data.len() as u64);
-
-                let length =
-                    id_data.len() + size_data.len() + timestamp_data.len() + data.len() + 1;
-                let mut ret = vec![0; length].into_boxed_slice();
+            RecordInfo::Data(data) => encode_data_into(self.id, self.timestamp, data, buf),
+        }
+    }
+}
 
-                let mut bitfield = 0;
+/// Appends a data record's binary representation to `buf`, without needing the data in a [`Record`].
+fn encode_data_into(id: u32, timestamp: u64, data: &[u8], buf: &mut Vec<u8>) {
+    encode_data_header_into(id, timestamp, data.len(), buf);
+    buf.extend_from_slice(data);
+}
 
-                // These HAVE to be u8's after the & 0x3/0x7 so there is no possible truncation
-                #[allow(clippy::cast_possible_truncation)]
-                {
-                    bitfield |= ((id_data.len() - 1) & 0x3) as u8;
-                    bitfield |= (((size_data.len() - 1) & 0x3) as u8) << 2;
-                    bitfield |= (((timestamp_data.len() - 1) & 0x7) as u8) << 4;
-                }
+/// The header of a data record with a `size` byte payload, which the caller appends right after.
+fn encode_data_header_into(id: u32, timestamp: u64, size: usize, buf: &mut Vec<u8>) {
+    debug_assert_ne!(id, 0, "Data records can't have ID 0 or stuff will go wrong");
 
-                ret[0] = bitfield;
+    let id_data = encode_int(id.into());
+    let size_data = encode_int(size as u64);
+    let timestamp_data = encode_int(timestamp);
 
-                let mut ptr = 1;
-                for data in id_data {
-                    ret[ptr] = data;
+    let mut bitfield = 0;
 
-                    ptr += 1;
-                }
+    // These HAVE to be u8's after the & 0x3/0x7 so there is no possible truncation
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        bitfield |= ((id_data.len() - 1) & 0x3) as u8;
+        bitfield |= (((size_data.len() - 1) & 0x3) as u8) << 2;
+        bitfield |= (((timestamp_data.len() - 1) & 0x7) as u8) << 4;
+    }
 
-                for data in size_data {
-                    ret[ptr] = data;
+    // Reserving the exact length means `Record::encode()` never has to reallocate to box this
+    buf.reserve_exact(1 + id_data.len() + size_data.len() + timestamp_data.len() + size);
+    buf.push(bitfield);
+    buf.extend_from_slice(&id_data);
+    buf.extend_from_slice(&size_data);
+    buf.extend_from_slice(&timestamp_data);
+}
 
-                    ptr += 1;
-                }
+impl<T: TimeProvider + Clone + Send + Sync> EntryHandle<T> {
+    /// Checks the entry hasn't been finished, and keeps it from being finished until the returned
+    /// guard is dropped, so hold on to it until the record has been sent.
+    fn check_open(&self) -> Result<RwLockReadGuard<'_, bool>> {
+        let finished = read(&self.finished);
+        if *finished {
+            Err(WriterError::EntryFinished(self.id).into())
+        } else {
+            Ok(finished)
+        }
+    }
 
-                for data in timestamp_data {
-                    ret[ptr] = data;
+    /// Sends the `Finish` record unless it already has been.
+    fn finish(&self) -> Result<()> {
+        let mut finished = write(&self.finished);
+        if *finished {
+            return Ok(());
+        }
+        *finished = true;
 
-                    ptr += 1;
-                }
+        if let Some(open_entries) = &self.open_entries {
+            lock(open_entries).remove(&self.id);
+        }
 
-                for data in data {
-                    ret[ptr] = *data;
+        let record = Record {
+            id: self.id,
+            timestamp: self.time_provider.get_time(),
+            info: RecordInfo::Control(ControlData::Finish),
+        };
 
-                    ptr += 1;
-                }
+        self.send(&record)
+    }
 
-                ret
-            }
+    fn send(&self, record: &Record) -> Result<()> {
+        match &self.queue {
+            Some(queue) => send_record(queue, record),
+            None => Ok(()),
         }
     }
 }
 
 impl<T: TimeProvider + Clone + Send + Sync> RawEntry<T> {
+    fn new(
+        id: u32,
+        queue: Option<Queue>,
+        time_provider: T,
+        open_entries: Option<OpenEntries>,
+        pool: Option<Arc<BufferPool>>,
+    ) -> RawEntry<T> {
+        RawEntry {
+            inner: Arc::new(EntryHandle {
+                id,
+                queue,
+                time_provider,
+                open_entries,
+                pool,
+                finished: Arc::new(RwLock::new(false)),
+            }),
+        }
+    }
+
+    pub(crate) fn time_provider(&self) -> &T {
+        &self.inner.time_provider
+    }
+
     /// Logs the data given as-is, without checking if it's the right format for the entry type.
     ///
     /// Automatically fetches timestamp from the `time_provider`
-    pub fn log_data(&self, data: Box<[u8]>) -> Result<()> {
-        self.log_data_with_timestamp(data, self.time_provider.get_time())
+    pub fn log_data(&self, data: Box<[u8]>) -> Result<usize> {
+        self.log_data_with_timestamp(data, self.inner.time_provider.get_time())
     }
 
     /// Logs the data given as-is, without checking if it's the right format for the entry type.
     ///
     /// Uses manually set timestamp instead of using the `time_provider`
-    pub fn log_data_with_timestamp(&self, data: Box<[u8]>, timestamp: u64) -> Result<()> {
-        let record = Record {
-            id: self.id,
-            timestamp,
-            info: RecordInfo::Data(data),
+    ///
+    /// Returns the number of payload bytes logged, which is 0 for a [`NullWriter`] since nothing is.
+    ///
+    /// # Errors
+    /// Returns [`WriterError::Closed`] if the [`WPILOGWriter`] has already been joined, or
+    /// [`WriterError::EntryFinished`] if the entry has been finished.
+    // Taking a box is kept for compatibility, `log_slice_with_timestamp()` is what actually logs it
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn log_data_with_timestamp(&self, data: Box<[u8]>, timestamp: u64) -> Result<usize> {
+        self.log_slice_with_timestamp(&data, timestamp)
+    }
+
+    /// Same as [`RawEntry::log_data()`], but the data doesn't have to be boxed first.
+    pub fn log_slice(&self, data: &[u8]) -> Result<usize> {
+        self.log_slice_with_timestamp(data, self.inner.time_provider.get_time())
+    }
+
+    /// Same as [`RawEntry::log_data_with_timestamp()`], but the data doesn't have to be boxed first.
+    ///
+    /// The data is encoded straight into the record, which with [`WPILOGWriterBuilder::buffer_pool()`]
+    /// means logging doesn't allocate at all.
+    ///
+    /// # Errors
+    /// Returns [`WriterError::Closed`] if the [`WPILOGWriter`] has already been joined, or
+    /// [`WriterError::EntryFinished`] if the entry has been finished.
+    pub fn log_slice_with_timestamp(&self, data: &[u8], timestamp: u64) -> Result<usize> {
+        self.log_encoded_with_timestamp(data.len(), timestamp, |buf| buf.extend_from_slice(data))
+    }
+
+    /// Like [`RawEntry::log_slice_with_timestamp()`], but `encode` appends the `size` byte payload to
+    /// the record itself, so it never has to be put together somewhere else first.
+    ///
+    /// # Errors
+    /// The same as [`RawEntry::log_slice_with_timestamp()`], or [`WpilogError::InvalidInput`] if
+    /// `encode` didn't append exactly `size` bytes (nothing is logged then).
+    pub(crate) fn log_encoded_with_timestamp(
+        &self,
+        size: usize,
+        timestamp: u64,
+        encode: impl FnOnce(&mut Vec<u8>),
+    ) -> Result<usize> {
+        let Some(queue) = &self.inner.queue else {
+            return Ok(0);
         };
+        let _open = self.inner.check_open()?;
 
-        self.channel.send(RecvState::Msg(record.encode()))?;
+        let mut buf = match &self.inner.pool {
+            Some(pool) => pool.take(),
+            None => vec![],
+        };
+        encode_data_header_into(self.inner.id, timestamp, size, &mut buf);
 
-        Ok(())
+        let start = buf.len();
+        encode(&mut buf);
+        if buf.len() - start != size {
+            return Err(WpilogError::InvalidInput(format!(
+                "Expected a {size} byte payload, but {} bytes were encoded",
+                buf.len() - start
+            )));
+        }
+
+        let msg = if self.inner.pool.is_some() {
+            RecvState::Pooled(buf)
+        } else {
+            RecvState::Msg(buf.into_boxed_slice())
+        };
+        queue.send(msg)?;
+
+        Ok(size)
+    }
+
+    /// Logs a record with no data, which marks that the entry has no value right now (as opposed to
+    /// a value of zero). [`DecodedValue::decode()`] reads these back as [`DecodedValue::Empty`].
+    ///
+    /// Typed entries don't do this themselves, wrap them in a
+    /// [`NullableEntry`](crate::entrytypes::NullableEntry) to log `Option`s.
+    ///
+    /// [`DecodedValue::decode()`]: crate::value::DecodedValue::decode
+    /// [`DecodedValue::Empty`]: crate::value::DecodedValue::Empty
+    pub fn log_empty(&self) -> Result<usize> {
+        self.log_slice(&[])
+    }
+
+    /// Sends the entry's `Finish` record now instead of when the last clone is dropped.
+    ///
+    /// Every clone shares this, so logging to any of them afterwards returns
+    /// [`WriterError::EntryFinished`]. Finishing more than once does nothing.
+    pub fn finish(&self) -> Result<()> {
+        self.inner.finish()
     }
 
     /// Updates the metadata for the entry, normally this is JSON but it *can* be anything.
     pub fn set_metadata(&self, metadata: Box<str>) -> Result<()> {
+        let _open = self.inner.check_open()?;
+
         let record = Record {
-            id: self.id,
-            timestamp: self.time_provider.get_time(),
+            id: self.inner.id,
+            timestamp: self.inner.time_provider.get_time(),
             info: RecordInfo::Control(ControlData::SetMetadata(metadata)),
         };
 
-        self.channel.send(RecvState::Msg(record.encode()))?;
-
-        Ok(())
+        self.inner.send(&record)
     }
 }
 
-impl<T: TimeProvider + Clone + Send + Sync> Drop for RawEntry<T> {
+impl<T: TimeProvider + Clone + Send + Sync> Drop for EntryHandle<T> {
     fn drop(&mut self) {
-        let record = Record {
-            id: self.id,
-            timestamp: self.time_provider.get_time(),
-            info: RecordInfo::Control(ControlData::Finish),
-        };
-
         // Best attempt at nice cleanup, if it fails oh well...
-        let _ = self.channel.send(RecvState::Msg(record.encode()));
+        let _ = self.finish();
     }
 }
@@ -0,0 +1,48 @@
+//! Exporting to Arrow, which needs the `arrow` feature.
+#![cfg(feature = "arrow")]
+
+mod common;
+
+use arrow_array::{Array, BooleanArray, Float64Array, UInt64Array};
+use common::NoopTimeProvider;
+use wpilog::{
+    entrytypes::{Entry, LogWriter},
+    export::to_arrow,
+    reader::WPILOGReader,
+    writer::WPILOGWriter,
+};
+
+#[test]
+fn scalars_are_forward_filled() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let voltage = writer.new_f64_entry("voltage".into(), None).unwrap();
+    let enabled = writer.new_bool_entry("enabled".into(), None).unwrap();
+    let mode = writer.new_string_entry("mode".into(), None).unwrap();
+
+    voltage.update_with_timestamp(12.0, 10).unwrap();
+    enabled.update_with_timestamp(true, 20).unwrap();
+    voltage.update_with_timestamp(11.5, 20).unwrap();
+    mode.update_with_timestamp("auto".into(), 25).unwrap();
+    enabled.update_with_timestamp(false, 30).unwrap();
+
+    let data = writer.join().unwrap();
+    let batch = to_arrow(WPILOGReader::new_raw(&data[..]).unwrap().typed()).unwrap();
+
+    let schema = batch.schema();
+    let names: Vec<_> = schema.fields().iter().map(|field| field.name()).collect();
+    assert_eq!(names, ["timestamp", "voltage", "enabled"]);
+
+    let column = |index: usize| batch.column(index).as_any();
+    let timestamps = column(0).downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(timestamps.values(), &[10, 20, 30]);
+
+    let voltage = column(1).downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(voltage.values(), &[12.0, 11.5, 11.5]);
+
+    let enabled = column(2).downcast_ref::<BooleanArray>().unwrap();
+    assert!(enabled.is_null(0));
+    assert_eq!(
+        (1..3).map(|row| enabled.value(row)).collect::<Vec<_>>(),
+        [true, false]
+    );
+}
@@ -0,0 +1,80 @@
+//! Fixture logs made with `helpers::build_log`.
+
+use std::io::{self, Write};
+use wpilog::{
+    helpers::{build_log, EntrySpec},
+    reader::WPILOGReader,
+    types::WpiType,
+    value::DecodedValue,
+};
+
+#[test]
+fn builds_readable_log() {
+    let entries = [
+        EntrySpec {
+            name: "count".into(),
+            type_str: "int64".into(),
+            samples: vec![
+                (30, 2i64.to_le_bytes().into()),
+                (10, 1i64.to_le_bytes().into()),
+            ],
+        },
+        EntrySpec {
+            name: "mode".into(),
+            type_str: "string".into(),
+            samples: vec![(20, b"auto".as_slice().into())],
+        },
+    ];
+
+    let data = build_log(Vec::new(), &entries).unwrap();
+    let mut typed = WPILOGReader::new_raw(&data[..]).unwrap().typed();
+
+    let samples: Vec<_> = typed
+        .by_ref()
+        .map(|record| {
+            let record = record.unwrap();
+            (record.timestamp, record.name.to_string(), record.value)
+        })
+        .collect();
+    assert_eq!(
+        samples,
+        [
+            (10, "count".into(), DecodedValue::Int64(1)),
+            (20, "mode".into(), DecodedValue::String("auto".into())),
+            (30, "count".into(), DecodedValue::Int64(2)),
+        ]
+    );
+
+    let schema = typed.schema();
+    let mut types: Vec<_> = schema
+        .entries()
+        .values()
+        .map(|entry| (entry.name.to_string(), entry.r#type.clone()))
+        .collect();
+    types.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        types,
+        [
+            ("count".into(), WpiType::Int64),
+            ("mode".into(), WpiType::String)
+        ]
+    );
+    assert!(!schema.is_open(1) && !schema.is_open(2));
+}
+
+struct FailingSink;
+
+impl Write for FailingSink {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("sink is broken"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn failing_sink_is_an_error() {
+    assert!(build_log(FailingSink, &[]).is_err());
+}
@@ -0,0 +1,90 @@
+//! Locks in that everything is little endian, whatever the target is.
+//!
+//! These only mean something on a big endian target too, which can be run through emulation with
+//! [`cross`](https://github.com/cross-rs/cross): `cross test --target powerpc64-unknown-linux-gnu`
+
+mod common;
+
+use common::NoopTimeProvider;
+use wpilog::{
+    entrytypes::{Entry, LogWriter},
+    reader::WPILOGReader,
+    value::{decode_f64, decode_i64, decode_string_array},
+    writer::WPILOGWriter,
+};
+
+/// Logs one value with `log` and returns the header and the data record's payload.
+fn logged_bytes(log: impl FnOnce(&WPILOGWriter<NoopTimeProvider, Vec<u8>>)) -> (Vec<u8>, Vec<u8>) {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    log(&writer);
+    let data = writer.join().unwrap();
+
+    let header = data[..12].to_vec();
+    let payload = WPILOGReader::new_raw(&data[..])
+        .unwrap()
+        .find(|record| record.id != 0)
+        .unwrap()
+        .data
+        .into_vec();
+
+    (header, payload)
+}
+
+#[test]
+fn header_is_little_endian() {
+    let (header, _) = logged_bytes(|writer| {
+        writer
+            .new_bool_entry("bool".into(), None)
+            .unwrap()
+            .update(true)
+            .unwrap();
+    });
+
+    assert_eq!(header, b"WPILOG\x00\x01\x00\x00\x00\x00");
+}
+
+#[test]
+fn numbers_are_little_endian() {
+    let (_, payload) = logged_bytes(|writer| {
+        writer
+            .new_i64_entry("int".into(), None)
+            .unwrap()
+            .update(0x0102_0304_0506_0708)
+            .unwrap();
+    });
+    assert_eq!(payload, [8, 7, 6, 5, 4, 3, 2, 1]);
+    assert_eq!(decode_i64(&payload).unwrap(), 0x0102_0304_0506_0708);
+
+    let (_, payload) = logged_bytes(|writer| {
+        writer
+            .new_f64_entry("double".into(), None)
+            .unwrap()
+            .update(1.0)
+            .unwrap();
+    });
+    assert_eq!(payload, [0, 0, 0, 0, 0, 0, 0xf0, 0x3f]);
+    assert_eq!(decode_f64(&payload).unwrap(), 1.0);
+
+    let (_, payload) = logged_bytes(|writer| {
+        writer
+            .new_f32_array_entry("floats".into(), None)
+            .unwrap()
+            .update(&[1.0, -2.0])
+            .unwrap();
+    });
+    assert_eq!(payload, [0, 0, 0x80, 0x3f, 0, 0, 0, 0xc0]);
+}
+
+#[test]
+fn string_array_lengths_are_little_endian() {
+    let (_, payload) = logged_bytes(|writer| {
+        writer
+            .new_string_array_entry("strings".into(), None)
+            .unwrap()
+            .update(&["ab"])
+            .unwrap();
+    });
+
+    assert_eq!(payload, [1, 0, 0, 0, 2, 0, 0, 0, b'a', b'b']);
+    assert_eq!(decode_string_array(&payload).unwrap(), ["ab"]);
+}
@@ -0,0 +1,34 @@
+//! Shared by the integration tests, each one only uses some of it.
+#![allow(dead_code)]
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use wpilog::writer::TimeProvider;
+
+/// Every record is logged at timestamp 0, unless it's given its own.
+#[derive(Copy, Clone, Debug)]
+pub struct NoopTimeProvider {}
+
+impl TimeProvider for NoopTimeProvider {
+    fn get_time(&self) -> u64 {
+        0
+    }
+}
+
+/// A clock that only moves when it's set, clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn set(&self, time: u64) {
+        self.0.store(time, Ordering::Relaxed);
+    }
+}
+
+impl TimeProvider for ManualClock {
+    fn get_time(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
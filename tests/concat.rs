@@ -0,0 +1,140 @@
+//! Reading a directory of log segments as one log.
+
+mod common;
+
+use common::ManualClock;
+use std::{fs, path::PathBuf};
+use wpilog::{
+    entrytypes::{Entry, LogWriter},
+    reader::{ConcatReader, SegmentOrder},
+    writer::WPILOGWriter,
+    ControlData, Record, RecordInfo,
+};
+
+/// A directory that's removed again once the test is done.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("wpilog-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn write(&self, name: &str, data: &[u8]) {
+        fs::write(self.0.join(name), data).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes a directory where the files' name order and the order they were written in differ:
+/// - `1.wpilog` and `2.wpilog`: a log of `x` rotated once, starting at 200
+/// - `3.wpilog`: an earlier log of `y` from 100, which reuses `x`'s id
+/// - `empty.wpilog` without any records, and `notes.txt` which isn't a log at all
+fn segments(dir: &TempDir) {
+    let clock = ManualClock::default();
+    clock.set(200);
+    let writer = WPILOGWriter::new(Vec::new(), clock.clone());
+    let x = writer.new_i64_entry("x".into(), None).unwrap();
+    x.update(1).unwrap();
+    dir.write("1.wpilog", &writer.rotate(Vec::new()).unwrap());
+    clock.set(300);
+    x.update(2).unwrap();
+    drop(x);
+    dir.write("2.wpilog", &writer.join().unwrap());
+
+    clock.set(100);
+    let writer = WPILOGWriter::new(Vec::new(), clock.clone());
+    writer
+        .new_i64_entry("y".into(), None)
+        .unwrap()
+        .update(7)
+        .unwrap();
+    dir.write("3.wpilog", &writer.join().unwrap());
+
+    dir.write(
+        "empty.wpilog",
+        &WPILOGWriter::new(Vec::new(), clock).join().unwrap(),
+    );
+    dir.write("notes.txt", b"not a log");
+    fs::create_dir(dir.0.join("nested.wpilog")).unwrap();
+}
+
+/// Every record as `(id, what it is)`.
+fn describe(reader: ConcatReader<impl std::io::Read>) -> Vec<(u32, String)> {
+    reader
+        .map(|record| {
+            let record: Record = record.try_into().unwrap();
+            let description = match record.info {
+                RecordInfo::Control(ControlData::Start { name, .. }) => format!("start {name}"),
+                RecordInfo::Control(ControlData::Finish) => "finish".into(),
+                RecordInfo::Control(ControlData::SetMetadata(_)) => "metadata".into(),
+                RecordInfo::Data(data) => {
+                    i64::from_le_bytes(data[..].try_into().unwrap()).to_string()
+                }
+            };
+            (record.id, description)
+        })
+        .collect()
+}
+
+fn expected(records: &[(u32, &str)]) -> Vec<(u32, String)> {
+    records
+        .iter()
+        .map(|&(id, what)| (id, what.into()))
+        .collect()
+}
+
+#[test]
+fn open_dir_reads_in_name_order() {
+    let dir = TempDir::new("concat-name");
+    segments(&dir);
+
+    // The `Start` repeated at the top of `2.wpilog` is skipped, but `y` restarting id 1 isn't
+    let records = describe(ConcatReader::open_dir(&dir.0).unwrap());
+    assert_eq!(
+        records,
+        expected(&[
+            (1, "start x"),
+            (1, "1"),
+            (1, "2"),
+            (1, "finish"),
+            (1, "start y"),
+            (1, "7"),
+            (1, "finish"),
+        ])
+    );
+}
+
+#[test]
+fn open_dir_sorted_by_first_timestamp() {
+    let dir = TempDir::new("concat-timestamp");
+    segments(&dir);
+
+    let records =
+        describe(ConcatReader::open_dir_sorted(&dir.0, SegmentOrder::FirstTimestamp).unwrap());
+    assert_eq!(
+        records,
+        expected(&[
+            (1, "start y"),
+            (1, "7"),
+            (1, "finish"),
+            (1, "start x"),
+            (1, "1"),
+            (1, "2"),
+            (1, "finish"),
+        ])
+    );
+}
+
+#[test]
+fn open_dir_of_missing_dir_is_an_error() {
+    let dir = TempDir::new("concat-missing");
+    assert!(ConcatReader::open_dir(dir.0.join("missing")).is_err());
+}
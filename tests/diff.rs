@@ -0,0 +1,86 @@
+//! Comparing the entries of two logs.
+
+mod common;
+
+use common::NoopTimeProvider;
+use std::collections::BTreeMap;
+use wpilog::{
+    diff::{compare, CountChange, LogDiff},
+    entrytypes::{Entry, LogWriter, RawBacked},
+    reader::WPILOGReader,
+    writer::WPILOGWriter,
+};
+
+/// Logs `count` values to a new `int64` entry, then finishes it.
+fn log_values(writer: &WPILOGWriter<NoopTimeProvider, Vec<u8>>, name: &str, count: i64) {
+    let entry = writer.new_i64_entry(name.into(), None).unwrap();
+    for i in 0..count {
+        entry.update(i).unwrap();
+    }
+    entry.raw_entry().finish().unwrap();
+}
+
+#[test]
+fn finds_every_kind_of_difference() {
+    let a = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    log_values(&a, "same", 4);
+    log_values(&a, "removed", 2);
+    log_values(&a, "count", 5);
+    log_values(&a, "restarted", 3);
+    log_values(&a, "restarted_more", 3);
+
+    let b = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    log_values(&b, "same", 4);
+    log_values(&b, "added", 0);
+    log_values(&b, "count", 8);
+    // Each restart gets a new id, but they're all the same entry
+    log_values(&b, "restarted", 2);
+    log_values(&b, "restarted", 1);
+    log_values(&b, "restarted_more", 3);
+    log_values(&b, "restarted_more", 3);
+
+    let a = a.join().unwrap();
+    let b = b.join().unwrap();
+    let diff = compare(
+        &mut WPILOGReader::new_raw(&a[..]).unwrap(),
+        &mut WPILOGReader::new_raw(&b[..]).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        diff,
+        LogDiff {
+            added: vec!["added".into()],
+            removed: vec!["removed".into()],
+            count_changes: BTreeMap::from([
+                ("count".into(), CountChange { a: 5, b: 8 }),
+                ("restarted_more".into(), CountChange { a: 3, b: 6 }),
+            ]),
+        }
+    );
+    assert_eq!(diff.count_changes["count"].delta(), 3);
+
+    // And the other way around
+    let diff = compare(
+        &mut WPILOGReader::new_raw(&b[..]).unwrap(),
+        &mut WPILOGReader::new_raw(&a[..]).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(diff.added, ["removed"]);
+    assert_eq!(diff.removed, ["added"]);
+    assert_eq!(diff.count_changes["count"].delta(), -3);
+}
+
+#[test]
+fn same_log_has_no_differences() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    log_values(&writer, "value", 3);
+    let data = writer.join().unwrap();
+
+    let diff = compare(
+        &mut WPILOGReader::new_raw(&data[..]).unwrap(),
+        &mut WPILOGReader::new_raw(&data[..]).unwrap(),
+    )
+    .unwrap();
+    assert!(diff.is_empty());
+}
@@ -0,0 +1,172 @@
+//! Golden outputs of the CSV and JSON Lines exports.
+
+use std::sync::Arc;
+use wpilog::{
+    export::{write_csv, write_jsonl, FloatFormat},
+    schema::TypedRecord,
+    value::DecodedValue,
+};
+
+fn records(values: Vec<(&str, DecodedValue)>) -> Vec<TypedRecord> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(timestamp, (name, value))| TypedRecord {
+            timestamp: timestamp as u64,
+            name: Arc::from(name),
+            value,
+        })
+        .collect()
+}
+
+fn csv(records: &[TypedRecord], floats: FloatFormat) -> String {
+    let out = write_csv(records.iter().cloned().map(Ok), Vec::new(), floats).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn jsonl(records: &[TypedRecord], floats: FloatFormat) -> String {
+    let out = write_jsonl(records.iter().cloned().map(Ok), Vec::new(), floats).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn floats() -> Vec<TypedRecord> {
+    records(vec![
+        ("double", DecodedValue::Double(0.1 + 0.2)),
+        ("float", DecodedValue::Float(0.1)),
+        ("large", DecodedValue::Double(123_456.789)),
+        ("array", DecodedValue::DoubleArray(vec![1.0 / 3.0, 2.5])),
+    ])
+}
+
+#[test]
+fn lossless_floats() {
+    let floats = floats();
+
+    assert_eq!(
+        csv(&floats, FloatFormat::Lossless),
+        "timestamp,name,value\n\
+         0,double,0.30000000000000004\n\
+         1,float,0.1\n\
+         2,large,123456.789\n\
+         3,array,\"[0.3333333333333333,2.5]\"\n"
+    );
+    assert_eq!(
+        jsonl(&floats, FloatFormat::Lossless),
+        r#"{"timestamp":0,"name":"double","value":0.30000000000000004}
+{"timestamp":1,"name":"float","value":0.1}
+{"timestamp":2,"name":"large","value":123456.789}
+{"timestamp":3,"name":"array","value":[0.3333333333333333,2.5]}
+"#
+    );
+}
+
+#[test]
+fn significant_floats() {
+    let floats = floats();
+
+    assert_eq!(
+        csv(&floats, FloatFormat::Significant(3)),
+        "timestamp,name,value\n\
+         0,double,0.3\n\
+         1,float,0.1\n\
+         2,large,123000\n\
+         3,array,\"[0.333,2.5]\"\n"
+    );
+    assert_eq!(
+        jsonl(&floats, FloatFormat::Significant(3)),
+        r#"{"timestamp":0,"name":"double","value":0.3}
+{"timestamp":1,"name":"float","value":0.1}
+{"timestamp":2,"name":"large","value":123000}
+{"timestamp":3,"name":"array","value":[0.333,2.5]}
+"#
+    );
+}
+
+#[test]
+fn fixed_floats() {
+    let floats = floats();
+
+    assert_eq!(
+        csv(&floats, FloatFormat::Fixed(2)),
+        "timestamp,name,value\n\
+         0,double,0.30\n\
+         1,float,0.10\n\
+         2,large,123456.79\n\
+         3,array,\"[0.33,2.50]\"\n"
+    );
+    assert_eq!(
+        jsonl(&floats, FloatFormat::Fixed(2)),
+        r#"{"timestamp":0,"name":"double","value":0.30}
+{"timestamp":1,"name":"float","value":0.10}
+{"timestamp":2,"name":"large","value":123456.79}
+{"timestamp":3,"name":"array","value":[0.33,2.50]}
+"#
+    );
+}
+
+#[test]
+fn non_finite_floats() {
+    let records = records(vec![
+        ("inf", DecodedValue::Double(f64::INFINITY)),
+        ("nan", DecodedValue::Float(f32::NAN)),
+        (
+            "array",
+            DecodedValue::DoubleArray(vec![f64::NEG_INFINITY, 1.0]),
+        ),
+    ]);
+
+    // CSV can hold them, JSON can't
+    for (floats, one) in [
+        (FloatFormat::Lossless, "1.0"),
+        (FloatFormat::Significant(3), "1"),
+        (FloatFormat::Fixed(2), "1.00"),
+    ] {
+        assert_eq!(
+            csv(&records, floats),
+            format!(
+                "timestamp,name,value\n\
+                 0,inf,inf\n\
+                 1,nan,NaN\n\
+                 2,array,\"[null,{one}]\"\n"
+            )
+        );
+        assert_eq!(
+            jsonl(&records, floats),
+            format!(
+                "{{\"timestamp\":0,\"name\":\"inf\",\"value\":null}}\n\
+                 {{\"timestamp\":1,\"name\":\"nan\",\"value\":null}}\n\
+                 {{\"timestamp\":2,\"name\":\"array\",\"value\":[null,{one}]}}\n"
+            )
+        );
+    }
+}
+
+#[test]
+fn strings_are_escaped() {
+    let records = records(vec![
+        (
+            "say \"hi\", robot",
+            DecodedValue::String("line\nbreak\ttab\\slash\r\u{1}".into()),
+        ),
+        (
+            "names",
+            DecodedValue::StringArray(vec!["a\"b".into(), "é".into()]),
+        ),
+        ("json", DecodedValue::Json(r#"{"a":1}"#.into())),
+    ]);
+
+    assert_eq!(
+        csv(&records, FloatFormat::Lossless),
+        "timestamp,name,value\n\
+         0,\"say \"\"hi\"\", robot\",\"line\nbreak\ttab\\slash\r\u{1}\"\n\
+         1,names,\"[\"\"a\\\"\"b\"\",\"\"é\"\"]\"\n\
+         2,json,\"{\"\"a\"\":1}\"\n"
+    );
+    assert_eq!(
+        jsonl(&records, FloatFormat::Lossless),
+        r#"{"timestamp":0,"name":"say \"hi\", robot","value":"line\nbreak\ttab\\slash\r\u0001"}
+{"timestamp":1,"name":"names","value":["a\"b","é"]}
+{"timestamp":2,"name":"json","value":"{\"a\":1}"}
+"#
+    );
+}
@@ -0,0 +1,36 @@
+#!/bin/sh
+# Regenerates ../datalog_writer.wpilog by building main.cpp against WPILib's own DataLogWriter.
+#
+# The wpiutil sources come from the allwpilib tree vendored in the ntcore-sys crate, since that's
+# the copy of them on crates.io. Needs curl, tar and a C++20 compiler.
+set -eu
+
+CRATE=ntcore-sys-0.3.0
+SHA256=797797f4a9cb7cdc8af81c9320e5e61995934f9ee155c18a5a58f849c58d0e5e
+
+here=$(cd "$(dirname "$0")" && pwd)
+work=$(mktemp -d)
+trap 'rm -rf "$work"' EXIT
+
+curl -sSfL -o "$work/crate.tar.gz" "https://static.crates.io/crates/ntcore-sys/$CRATE.crate"
+echo "$SHA256  $work/crate.tar.gz" | sha256sum -c - >/dev/null
+tar xzf "$work/crate.tar.gz" -C "$work"
+
+native="$work/$CRATE/allwpilib/wpiutil/src/main/native"
+third="$native/thirdparty"
+llvm="$third/llvm/cpp/llvm"
+
+${CXX:-c++} -std=c++20 -O1 \
+    -I"$native/include" -I"$third/llvm/include" -I"$third/fmtlib/include" \
+    -I"$third/expected/include" -I"$third/sigslot/include" -I"$third/memory/include" \
+    -I"$third/debugging/include" -I"$third/nanopb/include" -I"$third/protobuf/include" \
+    -I"$third/json/include" -I"$third/mpack/include" \
+    "$here/main.cpp" \
+    "$native/cpp/DataLog.cpp" "$native/cpp/DataLogWriter.cpp" "$native/cpp/Logger.cpp" \
+    "$native/cpp/timestamp.cpp" "$native/cpp/fs.cpp" \
+    "$llvm/SmallVector.cpp" "$llvm/MemAlloc.cpp" "$llvm/raw_ostream.cpp" \
+    "$llvm/ErrorHandling.cpp" "$llvm/ConvertUTF.cpp" "$llvm/ConvertUTFWrapper.cpp" \
+    "$third/fmtlib/src/format.cpp" "$third/fmtlib/src/os.cpp" \
+    -o "$work/datalog_writer"
+
+"$work/datalog_writer" "$here/../datalog_writer.wpilog"
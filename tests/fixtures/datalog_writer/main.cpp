@@ -0,0 +1,50 @@
+// Writes the log given as the only argument with WPILib's own DataLogWriter, see generate.sh.
+//
+// What it logs is listed at the top of tests/interop.rs, which checks this crate against it.
+
+#include <cstdio>
+#include <string>
+#include <system_error>
+#include <vector>
+
+#include <wpi/DataLogWriter.h>
+
+int main(int argc, char** argv) {
+  if (argc != 2) {
+    std::fprintf(stderr, "usage: %s <output.wpilog>\n", argv[0]);
+    return 2;
+  }
+
+  std::error_code ec;
+  wpi::log::DataLogWriter log{argv[1], ec};
+  if (ec) {
+    std::fprintf(stderr, "couldn't open %s: %s\n", argv[1], ec.message().c_str());
+    return 1;
+  }
+
+  int enabled = log.Start("robot/enabled", "boolean", "", 1000);
+  int count = log.Start("robot/count", "int64", R"({"source":"test"})", 1000);
+  int voltage = log.Start("robot/voltage", "double", "", 1000);
+  int mode = log.Start("robot/mode", "string", "", 1000);
+  int ids = log.Start("robot/ids", "int64[]", "", 1000);
+  int names = log.Start("robot/names", "string[]", "", 1000);
+
+  const std::vector<int64_t> some_ids{1, -2, 300000};
+  const std::vector<std::string> some_names{"a", "bc"};
+  log.AppendBoolean(enabled, true, 20000);
+  log.AppendInteger(count, 42, 20000);
+  log.AppendDouble(voltage, 12.5, 20000);
+  log.AppendString(mode, "auto", 20000);
+  log.AppendIntegerArray(ids, some_ids, 20000);
+  log.AppendStringArray(names, some_names, 20000);
+
+  log.AppendBoolean(enabled, false, 40000);
+  log.AppendInteger(count, -1, 40000);
+  log.SetMetadata(voltage, R"({"unit":"V"})", 40000);
+
+  log.AppendIntegerArray(ids, std::vector<int64_t>{}, 300000);
+  log.Finish(mode, 300000);
+
+  log.Flush();
+  return 0;
+}
@@ -0,0 +1,152 @@
+//! Finishing entries, one at a time or all at once.
+
+mod common;
+
+use common::NoopTimeProvider;
+use std::{collections::HashMap, sync::Barrier, thread};
+use wpilog::{
+    entrytypes::{Entry, LogWriter, RawBacked},
+    error::WpilogError,
+    reader::WPILOGReader,
+    writer::{WPILOGWriterBuilder, WriterError},
+    ControlData, Record, RecordInfo,
+};
+
+/// How many `Finish` records the log has for each id.
+fn finish_counts(data: &[u8]) -> HashMap<u32, usize> {
+    let mut counts = HashMap::new();
+
+    for record in WPILOGReader::new_raw(data).unwrap() {
+        let record: Record = record.try_into().unwrap();
+        if let RecordInfo::Control(ControlData::Finish) = record.info {
+            *counts.entry(record.id).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+#[test]
+fn finish_all_races_with_finish() {
+    for _ in 0..50 {
+        let writer = WPILOGWriterBuilder::new()
+            .track_entries(true)
+            .build(Vec::new(), NoopTimeProvider {})
+            .unwrap();
+        let entries: Vec<_> = (0..200)
+            .map(|i| writer.new_i64_entry(format!("entry{i}"), None).unwrap())
+            .collect();
+        let barrier = Barrier::new(5);
+
+        thread::scope(|scope| {
+            for chunk in entries.chunks(50) {
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    barrier.wait();
+                    for entry in chunk {
+                        entry.raw_entry().finish().unwrap();
+                    }
+                });
+            }
+
+            barrier.wait();
+            writer.finish_all().unwrap();
+        });
+
+        let counts = finish_counts(&writer.join().unwrap());
+        assert_eq!(counts.len(), 200);
+        assert!(counts.values().all(|&count| count == 1), "{counts:?}");
+    }
+}
+
+#[test]
+fn logging_after_finish_is_rejected() {
+    let writer = WPILOGWriterBuilder::new()
+        .track_entries(true)
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+    let finished = writer.new_i64_entry("finished".into(), None).unwrap();
+    let finished_by_all = writer
+        .new_i64_entry("finished_by_all".into(), None)
+        .unwrap();
+
+    finished.raw_entry().finish().unwrap();
+    writer.finish_all().unwrap();
+
+    for (entry, id) in [(&finished, 1), (&finished_by_all, 2)] {
+        assert!(matches!(
+            entry.update(1),
+            Err(WpilogError::Writer(WriterError::EntryFinished(finished_id))) if finished_id == id
+        ));
+    }
+
+    // Just the two Start and two Finish records
+    let data = writer.join().unwrap();
+    let records = WPILOGReader::new_raw(&data[..]).unwrap();
+    assert!(records.map(|record| record.is_control()).eq([true; 4]));
+}
+
+#[test]
+fn nothing_is_logged_after_finish() {
+    for _ in 0..50 {
+        let writer = WPILOGWriterBuilder::new()
+            .track_entries(true)
+            .build(Vec::new(), NoopTimeProvider {})
+            .unwrap();
+        let entry = writer.new_i64_entry("racing".into(), None).unwrap();
+        let barrier = Barrier::new(5);
+
+        let logged: usize = thread::scope(|scope| {
+            let loggers: Vec<_> = (0..4)
+                .map(|_| {
+                    let (barrier, entry) = (&barrier, entry.clone());
+                    scope.spawn(move || {
+                        barrier.wait();
+                        let mut logged = 0;
+                        loop {
+                            match entry.update(1) {
+                                Ok(_) => logged += 1,
+                                Err(WpilogError::Writer(WriterError::EntryFinished(_))) => {
+                                    return logged;
+                                }
+                                Err(err) => panic!("{err}"),
+                            }
+                        }
+                    })
+                })
+                .collect();
+            let metadata = scope.spawn(|| {
+                barrier.wait();
+                while entry.raw_entry().set_metadata("{}".into()).is_ok() {}
+            });
+
+            // Let them get going, then finish the entry from here while they still log
+            thread::sleep(std::time::Duration::from_millis(1));
+            entry.raw_entry().finish().unwrap();
+
+            metadata.join().unwrap();
+            loggers
+                .into_iter()
+                .map(|logger| logger.join().unwrap())
+                .sum()
+        });
+        drop(entry);
+
+        let records: Vec<Record> = WPILOGReader::new_raw(&writer.join().unwrap()[..])
+            .unwrap()
+            .map(|record| record.try_into().unwrap())
+            .collect();
+        let last = records.last().unwrap();
+        assert!(matches!(
+            last.info,
+            RecordInfo::Control(ControlData::Finish)
+        ));
+
+        // Everything that was logged made it in, and before the Finish
+        let data = records
+            .iter()
+            .filter(|record| matches!(record.info, RecordInfo::Data(_)))
+            .count();
+        assert_eq!(data, logged);
+    }
+}
@@ -0,0 +1,212 @@
+//! Streaming to a local server through `NetworkSink`, with the connection dropped along the way.
+
+mod common;
+
+use common::ManualClock;
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+    time::Duration,
+};
+use wpilog::{
+    entrytypes::{Entry, LogWriter, RawBacked},
+    net::NetworkSink,
+    reader::WPILOGReader,
+    validate::validate,
+    writer::WPILOGWriter,
+    ControlData, Record, RecordInfo,
+};
+
+/// Splits a log into its header and each record's bytes.
+fn split(data: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let header_len = 12 + u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+    let records = WPILOGReader::new_raw(data)
+        .unwrap()
+        .records_raw()
+        .map(Vec::from)
+        .collect();
+
+    (data[..header_len].to_vec(), records)
+}
+
+/// A log with `changing` (which gets metadata), `finished` (finished early) and `count` values.
+fn source_log() -> Vec<u8> {
+    let writer = WPILOGWriter::new(Vec::new(), ManualClock::default());
+    let changing = writer.new_i64_entry("changing".into(), None).unwrap();
+    let finished = writer.new_i64_entry("finished".into(), None).unwrap();
+
+    changing
+        .raw_entry()
+        .set_metadata(r#"{"unit":"m"}"#.into())
+        .unwrap();
+    finished.update(1).unwrap();
+    finished.raw_entry().finish().unwrap();
+    for i in 0..200 {
+        changing.update(i).unwrap();
+    }
+    drop(changing);
+
+    writer.join().unwrap()
+}
+
+/// The control records a connection's log starts with, as the names of the entries started and the
+/// metadata each one was last set to.
+fn replayed(data: &[u8]) -> Vec<(String, String)> {
+    let mut replayed: Vec<(u32, String, String)> = vec![];
+
+    for record in WPILOGReader::new_raw(data).unwrap() {
+        let record: Record = record.try_into().unwrap();
+        match record.info {
+            RecordInfo::Control(ControlData::Start { name, metadata, .. }) => {
+                replayed.push((record.id, name.into(), metadata.into()));
+            }
+            RecordInfo::Control(ControlData::SetMetadata(metadata)) => {
+                let entry = replayed.iter_mut().find(|entry| entry.0 == record.id);
+                entry.unwrap().2 = metadata.into();
+            }
+            _ => break,
+        }
+    }
+
+    replayed
+        .into_iter()
+        .map(|(_, name, metadata)| (name, metadata))
+        .collect()
+}
+
+/// Accepts a connection for each limit in turn, reading it until the sink closes it or that many
+/// bytes have arrived.
+fn serve(listener: TcpListener, limits: Vec<Option<usize>>) -> thread::JoinHandle<Vec<Vec<u8>>> {
+    thread::spawn(move || {
+        limits
+            .into_iter()
+            .map(|limit| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut received = vec![];
+                let mut buf = [0; 4096];
+
+                while limit.map_or(true, |limit| received.len() < limit) {
+                    let read = stream.read(&mut buf).unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    received.extend_from_slice(&buf[..read]);
+                }
+
+                received
+            })
+            .collect()
+    })
+}
+
+fn is_valid_log(data: &[u8]) -> bool {
+    validate(&mut WPILOGReader::new_raw(data).unwrap()).is_ok()
+}
+
+#[test]
+fn reconnects_with_header_and_open_entries() {
+    let data = source_log();
+    let (header, records) = split(&data);
+    // Everything up to (and including) the `Finish` of `finished`, then the first 10 values
+    let first_part = 5 + 10;
+    let first_len = header.len() + records[..first_part].iter().map(Vec::len).sum::<usize>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = serve(listener, vec![Some(first_len), None]);
+
+    let mut sink = NetworkSink::new(addr).retry_interval(Duration::ZERO);
+
+    // Written in small pieces so records keep getting split between writes
+    let mut first = header.clone();
+    first.extend(records[..first_part].concat());
+    for chunk in first.chunks(3) {
+        sink.write_all(chunk).unwrap();
+    }
+    assert!(sink.is_connected());
+
+    // Once the server has everything so far it hangs up, which the sink notices a few writes later
+    let mut next = first_part;
+    while sink.connections() < 2 {
+        thread::sleep(Duration::from_millis(5));
+        sink.write_all(&records[next]).unwrap();
+        next += 1;
+    }
+    for record in &records[next..] {
+        sink.write_all(record).unwrap();
+    }
+    drop(sink);
+
+    let connections = server.join().unwrap();
+    assert_eq!(connections[0], first);
+
+    // The second connection is a log of its own, with `changing` and its metadata started again
+    let second = &connections[1];
+    assert!(second.starts_with(&header));
+    assert!(is_valid_log(second));
+    assert_eq!(
+        replayed(second),
+        [("changing".to_string(), r#"{"unit":"m"}"#.to_string())]
+    );
+
+    // It ends the same as the source log, some records from around the disconnect may be missing
+    let last = records.last().unwrap();
+    assert!(second.ends_with(last));
+}
+
+/// An address nothing is listening on (for now).
+fn closed_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[test]
+fn capped_backlog_still_replays_starts() {
+    let data = source_log();
+    let (header, records) = split(&data);
+    let addr = closed_addr();
+
+    let mut sink = NetworkSink::new(addr)
+        .retry_interval(Duration::ZERO)
+        .max_backlog(100);
+    sink.write_all(&header).unwrap();
+    for record in &records[..100] {
+        sink.write_all(record).unwrap();
+    }
+
+    // The Starts were dropped from the backlog with everything else, but still count as open
+    assert!(!sink.is_connected());
+    assert!(sink.dropped_records() > 0);
+
+    let server = serve(TcpListener::bind(addr).unwrap(), vec![None]);
+    for record in &records[100..] {
+        sink.write_all(record).unwrap();
+    }
+    assert_eq!(sink.connections(), 1);
+    drop(sink);
+
+    let connection = &server.join().unwrap()[0];
+    assert!(connection.starts_with(&header));
+    assert!(is_valid_log(connection));
+    assert_eq!(
+        replayed(connection),
+        [("changing".to_string(), r#"{"unit":"m"}"#.to_string())]
+    );
+    assert!(connection.ends_with(records.last().unwrap()));
+}
+
+#[test]
+fn garbage_is_dropped_instead_of_buffered() {
+    let mut sink = NetworkSink::new(closed_addr());
+    let (header, _) = split(&source_log());
+    sink.write_all(&header).unwrap();
+
+    // A record header claiming a 4 GiB payload, which would never be finished
+    sink.write_all(&[0x0c, 1, 0xff, 0xff, 0xff, 0xff, 0])
+        .unwrap();
+    sink.write_all(&vec![0; 64 * 1024 * 1024]).unwrap();
+    assert_eq!(sink.dropped_records(), 1);
+}
@@ -0,0 +1,106 @@
+//! Entries made and logged to from many threads at once still have their `Start` record first, and
+//! the reorder window sorts records logged out of order. Deterministic writers make the same bytes
+//! every time.
+
+mod common;
+
+use common::NoopTimeProvider;
+use std::{sync::Arc, thread, time::Duration};
+use wpilog::{
+    entrytypes::{Entry, LogWriter},
+    reader::WPILOGReader,
+    validate::validate,
+    writer::{WPILOGWriter, WPILOGWriterBuilder},
+};
+
+#[test]
+fn start_precedes_data_across_threads() {
+    let writer = Arc::new(WPILOGWriter::new(Vec::new(), NoopTimeProvider {}));
+
+    let threads: Vec<_> = (0..8)
+        .map(|thread| {
+            let writer = Arc::clone(&writer);
+            thread::spawn(move || {
+                for i in 0..100 {
+                    let entry = writer
+                        .new_i64_entry(format!("thread{thread}/entry{i}"), None)
+                        .unwrap();
+
+                    // Log from yet another thread, which only gets the entry after it's made
+                    thread::spawn(move || entry.update(i).unwrap())
+                        .join()
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let writer = Arc::into_inner(writer).unwrap();
+    let log = writer.join().unwrap();
+
+    let report = validate(&mut WPILOGReader::new_raw(&log[..]).unwrap());
+    assert!(report.is_ok(), "{:?}", report.issues);
+    // A Start, a value and a Finish for every entry
+    assert_eq!(report.records, 8 * 100 * 3);
+}
+
+#[test]
+fn reorder_window_sorts_late_records() {
+    let writer = WPILOGWriterBuilder::new()
+        .reorder_window(Duration::from_micros(100))
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+    let entry = writer.new_i64_entry("value".into(), None).unwrap();
+
+    for timestamp in [10, 30, 20, 200, 150, 5] {
+        entry.update_with_timestamp(0, timestamp).unwrap();
+    }
+
+    let data = writer.join().unwrap();
+    let timestamps: Vec<u64> = WPILOGReader::new_raw(&data[..])
+        .unwrap()
+        .skip(1)
+        .map(|record| record.timestamp)
+        .collect();
+
+    // 5 is more than the window late, 10 to 30 were already written by then
+    assert_eq!(timestamps, [10, 20, 30, 5, 150, 200]);
+}
+
+#[test]
+fn deterministic_logs_are_reproducible() {
+    let log = || {
+        let writer = WPILOGWriterBuilder::new()
+            .deterministic(true)
+            .build(Vec::new(), NoopTimeProvider {})
+            .unwrap();
+
+        let entries: Vec<_> = (0..20)
+            .map(|i| writer.new_i64_entry(format!("entry{i}"), None).unwrap())
+            .collect();
+        for (i, entry) in (0..).zip(&entries) {
+            entry.update(i).unwrap();
+        }
+
+        let from_other_thread = thread::scope(|scope| {
+            scope
+                .spawn(|| writer.new_i64_entry("other".into(), None).is_err())
+                .join()
+                .unwrap()
+        });
+        assert!(from_other_thread);
+
+        writer.join().unwrap()
+    };
+
+    assert_eq!(log(), log());
+    assert!(WPILOGWriterBuilder::new()
+        .deterministic(true)
+        .wall_clock_start(true)
+        .build(Vec::new(), NoopTimeProvider {})
+        .is_err());
+}
@@ -0,0 +1,80 @@
+//! Decoding a log in memory on the rayon thread pool, which needs the `rayon` feature.
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use common::NoopTimeProvider;
+use wpilog::{
+    entrytypes::{Entry, LogWriter, RawBacked},
+    error::WpilogError,
+    reader::WPILOGReader,
+    slice::SliceReader,
+    writer::WPILOGWriter,
+};
+
+/// Thousands of records of several types, with an entry restarted as a different type part way.
+fn busy_log() -> Vec<u8> {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let count = writer.new_i64_entry("count".into(), None).unwrap();
+    let voltage = writer.new_f64_entry("voltage".into(), None).unwrap();
+    let names = writer.new_string_array_entry("names".into(), None).unwrap();
+    let mode = writer.new_string_entry("mode".into(), None).unwrap();
+
+    for i in 0..5000 {
+        count.update_with_timestamp(i, i as u64).unwrap();
+        voltage
+            .update_with_timestamp(i as f64 / 3.0, i as u64)
+            .unwrap();
+        if i % 7 == 0 {
+            names
+                .update_with_timestamp(&["a", &i.to_string()], i as u64)
+                .unwrap();
+        }
+        if i == 2500 {
+            mode.raw_entry().finish().unwrap();
+        }
+    }
+    let mode = writer.new_bool_entry("mode".into(), None).unwrap();
+    mode.update_with_timestamp(true, 5000).unwrap();
+    drop((count, voltage, names, mode));
+
+    writer.join().unwrap()
+}
+
+#[test]
+fn matches_typed_in_order() {
+    let data = busy_log();
+
+    let expected: Vec<_> = WPILOGReader::new_raw(&data[..])
+        .unwrap()
+        .typed()
+        .map(|record| {
+            let record = record.unwrap();
+            (record.timestamp, record.name, record.value)
+        })
+        .collect();
+    let records: Vec<_> = SliceReader::new(&data)
+        .unwrap()
+        .par_records()
+        .unwrap()
+        .into_iter()
+        .map(|record| (record.timestamp, record.name, record.value))
+        .collect();
+
+    assert_eq!(records.len(), 5000 + 5000 + 715 + 1);
+    assert_eq!(records, expected);
+}
+
+#[test]
+fn starts_from_the_current_record() {
+    let data = busy_log();
+    let mut reader = SliceReader::new(&data).unwrap();
+
+    // Without the `Start` of `count` its values can't be decoded
+    reader.next().unwrap();
+    let remaining = reader.clone().count();
+
+    let err = reader.par_records().unwrap_err();
+    assert!(matches!(err, WpilogError::InvalidRecord(_)), "{err:?}");
+    assert_eq!(reader.count(), remaining);
+}
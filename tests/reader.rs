@@ -0,0 +1,291 @@
+//! Reading logs beyond decoding records one by one: spans, checksums, tailing and malformed input.
+
+mod common;
+
+use common::ManualClock;
+use std::{
+    io::{self, Cursor, Read},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use wpilog::{
+    entrytypes::{Entry, LogWriter},
+    error::WpilogError,
+    reader::{ReaderOptions, WPILOGReader},
+    slice::SliceReader,
+    writer::{WPILOGWriter, WPILOGWriterBuilder},
+    HeaderFormat,
+};
+
+/// A log with records from timestamp 100 to 900, not all in order.
+fn unsorted_log() -> Vec<u8> {
+    let clock = ManualClock::default();
+    clock.set(300);
+    let writer = WPILOGWriter::new(Vec::new(), clock.clone());
+    let entry = writer.new_i64_entry("value".into(), None).unwrap();
+
+    for timestamp in [500, 100, 900, 200] {
+        entry.update_with_timestamp(0, timestamp).unwrap();
+    }
+    clock.set(400);
+    drop(entry);
+
+    writer.join().unwrap()
+}
+
+#[test]
+fn time_span_without_seeking() {
+    let data = unsorted_log();
+
+    // A plain slice can't seek, so this has to scan
+    let mut reader = WPILOGReader::new_raw(&data[..]).unwrap();
+    assert_eq!(reader.time_span().unwrap(), (100, 900));
+    assert!(reader.time_span().is_err());
+}
+
+#[test]
+fn time_span_from_index() {
+    let data = unsorted_log();
+
+    let mut reader = WPILOGReader::new_raw(Cursor::new(&data)).unwrap();
+    reader.build_index().unwrap();
+    assert_eq!(reader.time_span().unwrap(), (100, 900));
+
+    // The index is still there, and the reader hasn't moved
+    assert_eq!(reader.time_span().unwrap(), (100, 900));
+    assert_eq!(reader.count(), 6);
+}
+
+/// An empty log header followed by `records`.
+fn with_header(records: &[u8]) -> Vec<u8> {
+    let mut log = b"WPILOG\x00\x01\x00\x00\x00\x00".to_vec();
+    log.extend_from_slice(records);
+    log
+}
+
+#[test]
+fn read_record_truncated() {
+    // A 4 byte payload that's cut off after 2 bytes, then a header cut off after its id
+    for records in [&[0x00, 1, 4, 10, 0xaa, 0xbb][..], &[0x00, 1]] {
+        let data = with_header(records);
+        let mut reader = WPILOGReader::new_raw(&data[..]).unwrap();
+        let err = reader.read_record().unwrap_err();
+
+        assert!(
+            matches!(&err, WpilogError::Truncated(_))
+                || matches!(&err, WpilogError::Io(err) if err.kind() == std::io::ErrorKind::UnexpectedEof),
+            "{err:?}"
+        );
+    }
+}
+
+#[test]
+fn read_record_oversized() {
+    // A payload size of 2^32 - 1, from a 4 byte size field
+    let data = with_header(&[0x0c, 1, 0xff, 0xff, 0xff, 0xff, 10]);
+    let options = ReaderOptions {
+        max_record_size: 1024,
+        ..ReaderOptions::default()
+    };
+
+    let mut reader = WPILOGReader::with_options(&data[..], &options).unwrap();
+    assert!(matches!(
+        reader.read_record(),
+        Err(WpilogError::InvalidRecord(_))
+    ));
+}
+
+fn log_with_checksum(checksum: bool) -> Vec<u8> {
+    let writer = WPILOGWriterBuilder::new()
+        .checksum(checksum)
+        .build(Vec::new(), ManualClock::default())
+        .unwrap();
+    let entry = writer.new_i64_entry("value".into(), None).unwrap();
+    entry.update(0x0102_0304_0506_0708).unwrap();
+    drop(entry);
+
+    writer.join().unwrap()
+}
+
+fn verify(data: &[u8]) -> wpilog::error::Result<bool> {
+    WPILOGReader::new_raw(Cursor::new(data))
+        .unwrap()
+        .verify_checksum()
+}
+
+#[test]
+fn checksum_trailer_verifies() {
+    let data = log_with_checksum(true);
+    assert!(verify(&data).unwrap());
+
+    // Part of the value logged to `value`, so the log still reads fine
+    let mut flipped = data.clone();
+    let value = flipped
+        .windows(8)
+        .position(|window| window == 0x0102_0304_0506_0708i64.to_le_bytes())
+        .unwrap();
+    flipped[value] ^= 1;
+    assert!(!verify(&flipped).unwrap());
+
+    // Cut off in the middle of the trailer
+    assert!(!verify(&data[..data.len() - 3]).unwrap());
+}
+
+#[test]
+fn checksum_without_trailer_is_an_error() {
+    let data = log_with_checksum(false);
+    assert!(matches!(verify(&data), Err(WpilogError::Missing(_))));
+}
+
+/// A log in memory that another thread appends to, like a file that's still being written.
+#[derive(Clone, Default)]
+struct GrowingLog {
+    data: Arc<Mutex<Vec<u8>>>,
+    read: usize,
+}
+
+impl GrowingLog {
+    fn append(&self, bytes: &[u8]) {
+        self.data.lock().unwrap().extend_from_slice(bytes);
+    }
+}
+
+impl Read for GrowingLog {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.data.lock().unwrap();
+        let read = (&data[self.read..]).read(buf)?;
+        self.read += read;
+        Ok(read)
+    }
+}
+
+#[test]
+fn tailing_waits_for_partial_records() {
+    let log = GrowingLog::default();
+    log.append(&with_header(&[]));
+
+    let reader = WPILOGReader::new_raw(log.clone()).unwrap();
+    let (sender, records) = mpsc::channel();
+    thread::spawn(move || {
+        for record in reader.records_tailing(Duration::from_millis(1)) {
+            if sender.send(record).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Everything but the last byte of the payload
+    log.append(&[0x00, 1, 4, 10, 0xaa, 0xbb, 0xcc]);
+    assert!(records.recv_timeout(Duration::from_millis(100)).is_err());
+
+    log.append(&[0xdd]);
+    let record = records.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((record.id, record.timestamp), (1, 10));
+    assert_eq!(&*record.data, [0xaa, 0xbb, 0xcc, 0xdd]);
+}
+
+#[test]
+fn for_each_record_reports_errors() {
+    let records = [0x00, 1, 1, 10, 0xaa, 0x00, 2, 2, 20, 0xbb, 0xcc];
+    let collect = |data: &[u8], options: &ReaderOptions| {
+        let mut reader = WPILOGReader::with_options(data, options).unwrap();
+        let mut seen = vec![];
+        let result = reader.for_each_record(|record| seen.push((record.id, record.data.to_vec())));
+        (seen, result)
+    };
+    let both = vec![(1, vec![0xaa]), (2, vec![0xbb, 0xcc])];
+
+    let (seen, result) = collect(&with_header(&records), &ReaderOptions::default());
+    assert_eq!(seen, both);
+    assert!(result.is_ok());
+
+    // The last byte is missing, which isn't the same as ending cleanly
+    let (seen, result) = collect(
+        &with_header(&records[..records.len() - 1]),
+        &ReaderOptions::default(),
+    );
+    assert_eq!(seen, both[..1]);
+    assert!(
+        matches!(result, Err(WpilogError::Truncated(_))),
+        "{result:?}"
+    );
+
+    let options = ReaderOptions {
+        max_record_size: 1,
+        ..ReaderOptions::default()
+    };
+    let (seen, result) = collect(&with_header(&records), &options);
+    assert_eq!(seen, both[..1]);
+    assert!(
+        matches!(result, Err(WpilogError::InvalidRecord(_))),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn records_raw_truncated_huge_record() {
+    // Claims 2^32 - 1 bytes of payload but has one, with nothing bounding the size
+    let data = with_header(&[0x0c, 1, 0xff, 0xff, 0xff, 0xff, 10, 0xaa]);
+    let options = ReaderOptions {
+        max_record_size: usize::MAX,
+        ..ReaderOptions::default()
+    };
+
+    let reader = WPILOGReader::with_options(&data[..], &options).unwrap();
+    assert_eq!(reader.records_raw().count(), 0);
+}
+
+#[test]
+fn slice_reader_with_options() {
+    let header = HeaderFormat {
+        magic: *b"VENDOR",
+        version: 0x0203,
+    };
+    let writer = WPILOGWriterBuilder::new()
+        .header(header)
+        .build(Vec::new(), ManualClock::default())
+        .unwrap();
+    let entry = writer.new_string_entry("mode".into(), None).unwrap();
+    entry.update_with_timestamp("abc".into(), 10).unwrap();
+    drop(entry);
+    let data = writer.join().unwrap();
+
+    assert!(matches!(
+        SliceReader::new(&data),
+        Err(WpilogError::InvalidHeader)
+    ));
+
+    let options = ReaderOptions {
+        header,
+        ..ReaderOptions::default()
+    };
+    let records: Vec<_> = SliceReader::with_options(&data, &options)
+        .unwrap()
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].data, b"abc");
+
+    // Same limit as the streaming reader, the `Start` record is already over it
+    let options = ReaderOptions {
+        max_record_size: 2,
+        ..options
+    };
+    let mut reader = SliceReader::with_options(&data, &options).unwrap();
+    assert!(matches!(
+        reader.read_record(),
+        Err(WpilogError::InvalidRecord(_))
+    ));
+
+    let options = ReaderOptions {
+        header: HeaderFormat {
+            version: 0x0100,
+            ..header
+        },
+        ..ReaderOptions::default()
+    };
+    assert!(matches!(
+        SliceReader::with_options(&data, &options),
+        Err(WpilogError::InvalidVersion(0x0203))
+    ));
+}
@@ -0,0 +1,125 @@
+//! Logging through the [ring](wpilog::writer::WPILOGWriterBuilder::ring()) queue.
+
+mod common;
+
+use common::NoopTimeProvider;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Barrier},
+    thread,
+};
+use wpilog::{
+    entrytypes::{Entry, LogWriter, RawBacked},
+    reader::WPILOGReader,
+    writer::WPILOGWriterBuilder,
+    ControlData, Record, RecordInfo,
+};
+
+/// Every data value logged to each id, in log order.
+fn values_by_id(data: &[u8]) -> HashMap<u32, Vec<i64>> {
+    let mut values: HashMap<u32, Vec<i64>> = HashMap::new();
+
+    for record in WPILOGReader::new_raw(data).unwrap() {
+        if record.is_data() {
+            let value = i64::from_le_bytes(record.data[..].try_into().unwrap());
+            values.entry(record.id).or_default().push(value);
+        }
+    }
+
+    values
+}
+
+#[test]
+fn many_producers_lose_nothing() {
+    const PRODUCERS: usize = 8;
+    const VALUES: i64 = 10_000;
+
+    let writer = WPILOGWriterBuilder::new()
+        .ring(PRODUCERS * 10_000)
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+    let entries: Vec<_> = (0..PRODUCERS)
+        .map(|i| writer.new_i64_entry(format!("producer{i}"), None).unwrap())
+        .collect();
+    let barrier = Barrier::new(PRODUCERS);
+
+    thread::scope(|scope| {
+        for entry in &entries {
+            let barrier = &barrier;
+            scope.spawn(move || {
+                barrier.wait();
+                for i in 0..VALUES {
+                    entry.update(i).unwrap();
+                }
+            });
+        }
+    });
+    assert_eq!(writer.stats().dropped, 0);
+    drop(entries);
+
+    let values = values_by_id(&writer.join().unwrap());
+    assert_eq!(values.len(), PRODUCERS);
+    for values in values.values() {
+        assert!(values.iter().copied().eq(0..VALUES));
+    }
+}
+
+#[test]
+fn full_ring_drops_oldest_data() {
+    // Holds the worker back until everything is queued, so what gets dropped doesn't depend on timing
+    let release = Arc::new(Barrier::new(2));
+    let writer = WPILOGWriterBuilder::new()
+        .ring(10)
+        .on_worker_start({
+            let release = Arc::clone(&release);
+            move || {
+                release.wait();
+            }
+        })
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+
+    let first = writer.new_i64_entry("first".into(), None).unwrap();
+    for i in 0..50 {
+        first.update(i).unwrap();
+    }
+    // Control records go past the capacity instead of being dropped
+    let second = writer.new_i64_entry("second".into(), None).unwrap();
+    second.raw_entry().set_metadata("{}".into()).unwrap();
+    for i in 0..50 {
+        second.update(i).unwrap();
+    }
+    first.raw_entry().finish().unwrap();
+
+    // The control records count towards the capacity too, so only 9 values fit alongside them
+    assert_eq!(writer.stats().dropped, 91);
+    assert_eq!(writer.queue_len(), 13);
+
+    release.wait();
+    drop((first, second));
+    let data = writer.join().unwrap();
+
+    // Only the newest values are left, and every control record
+    let values = values_by_id(&data);
+    assert_eq!(values.len(), 1);
+    assert!(values[&2].iter().copied().eq(41..50));
+
+    let control: Vec<_> = WPILOGReader::new_raw(&data[..])
+        .unwrap()
+        .filter(|record| record.is_control())
+        .map(|record| match Record::try_from(record).unwrap().info {
+            RecordInfo::Control(control) => control,
+            RecordInfo::Data(_) => unreachable!(),
+        })
+        .collect();
+    assert!(matches!(
+        &control[..],
+        [
+            ControlData::Start { .. },
+            ControlData::Start { .. },
+            ControlData::SetMetadata(_),
+            ControlData::Finish,
+            ControlData::Finish,
+        ]
+    ));
+}
@@ -0,0 +1,109 @@
+//! Rotating the writer to new sinks part way through a log.
+
+mod common;
+
+use common::NoopTimeProvider;
+use wpilog::{
+    entrytypes::{Entry, LogWriter, RawBacked},
+    reader::WPILOGReader,
+    validate::validate,
+    writer::WPILOGWriterBuilder,
+    ControlData, Record, RecordInfo,
+};
+
+/// Every record of a segment, which has to be readable and valid on its own.
+fn read_segment(data: &[u8]) -> Vec<Record> {
+    let mut reader = WPILOGReader::new_raw(data).unwrap();
+    assert_eq!(&*reader.extra_header, b"robot");
+    assert!(validate(&mut reader).is_ok());
+
+    WPILOGReader::new_raw(data)
+        .unwrap()
+        .map(|record| record.try_into().unwrap())
+        .collect()
+}
+
+/// The `(id, name, metadata)` of a `Start`.
+type Start<'a> = (u32, &'a str, &'a str);
+
+/// Every `Start`, and the `(id, metadata)` of every `SetMetadata`.
+fn controls(records: &[Record]) -> (Vec<Start>, Vec<(u32, &str)>) {
+    let mut starts = vec![];
+    let mut metadata = vec![];
+
+    for record in records {
+        match &record.info {
+            RecordInfo::Control(ControlData::Start {
+                name, metadata: m, ..
+            }) => {
+                starts.push((record.id, &**name, &**m));
+            }
+            RecordInfo::Control(ControlData::SetMetadata(m)) => metadata.push((record.id, &**m)),
+            _ => {}
+        }
+    }
+
+    (starts, metadata)
+}
+
+fn values(records: &[Record]) -> Vec<(u32, i64)> {
+    records
+        .iter()
+        .filter_map(|record| match &record.info {
+            RecordInfo::Data(data) => {
+                Some((record.id, i64::from_le_bytes(data[..].try_into().unwrap())))
+            }
+            RecordInfo::Control(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn each_segment_is_a_standalone_log() {
+    let writer = WPILOGWriterBuilder::new()
+        .extra_header("robot")
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+
+    let kept = writer
+        .new_i64_entry("kept".into(), Some("{}".into()))
+        .unwrap();
+    let finished = writer.new_i64_entry("finished".into(), None).unwrap();
+    kept.raw_entry()
+        .set_metadata(r#"{"unit":"m"}"#.into())
+        .unwrap();
+    kept.update(1).unwrap();
+    finished.update(2).unwrap();
+    finished.raw_entry().finish().unwrap();
+
+    let first = writer.rotate(Vec::new()).unwrap();
+
+    kept.update(3).unwrap();
+    let late = writer.new_i64_entry("late".into(), None).unwrap();
+    late.update(4).unwrap();
+
+    let second = writer.rotate(Vec::new()).unwrap();
+
+    kept.update(5).unwrap();
+    drop((kept, finished, late));
+    let third = writer.join().unwrap();
+
+    let first = read_segment(&first);
+    let (starts, metadata) = controls(&first);
+    assert_eq!(starts, [(1, "kept", "{}"), (2, "finished", "")]);
+    assert_eq!(metadata, [(1, r#"{"unit":"m"}"#)]);
+    assert_eq!(values(&first), [(1, 1), (2, 2)]);
+
+    // Only entries still open are started again, with their latest metadata, then `late` starts
+    let second = read_segment(&second);
+    let (starts, metadata) = controls(&second);
+    assert_eq!(starts, [(1, "kept", "{}"), (3, "late", "")]);
+    assert_eq!(metadata, [(1, r#"{"unit":"m"}"#)]);
+    assert_eq!(values(&second), [(1, 3), (3, 4)]);
+
+    let third = read_segment(&third);
+    let (starts, metadata) = controls(&third);
+    assert_eq!(starts, [(1, "kept", "{}"), (3, "late", "")]);
+    assert_eq!(metadata, [(1, r#"{"unit":"m"}"#)]);
+    assert_eq!(values(&third), [(1, 5)]);
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use common::NoopTimeProvider;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use wpilog::{
+    entrytypes::{Entry, FiniteEntry, LogWriter, RawBacked},
+    error::WpilogError,
+    reader::WPILOGReader,
+    types::WpiType,
+    value::DecodedValue,
+    writer::{WPILOGWriter, WPILOGWriterBuilder},
+    ControlData, Record, RecordInfo,
+};
+
+fn float32() -> impl Strategy<Value = f32> {
+    use proptest::num::f32::{INFINITE, NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+fn float64() -> impl Strategy<Value = f64> {
+    use proptest::num::f64::{INFINITE, NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+fn value() -> impl Strategy<Value = DecodedValue> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..32)
+            .prop_map(|data| DecodedValue::Raw(data.into_boxed_slice())),
+        any::<bool>().prop_map(DecodedValue::Boolean),
+        any::<i64>().prop_map(DecodedValue::Int64),
+        float32().prop_map(DecodedValue::Float),
+        float64().prop_map(DecodedValue::Double),
+        ".*".prop_map(DecodedValue::String),
+        prop::collection::vec(any::<bool>(), 0..16).prop_map(DecodedValue::BooleanArray),
+        prop::collection::vec(any::<i64>(), 0..16).prop_map(DecodedValue::Int64Array),
+        prop::collection::vec(float32(), 0..16).prop_map(DecodedValue::FloatArray),
+        prop::collection::vec(float64(), 0..16).prop_map(DecodedValue::DoubleArray),
+        prop::collection::vec(".*", 0..8).prop_map(DecodedValue::StringArray),
+        ".*".prop_map(DecodedValue::Json),
+        prop::collection::vec(any::<u8>(), 0..32)
+            .prop_map(|data| DecodedValue::Msgpack(data.into_boxed_slice())),
+        Just(DecodedValue::Empty),
+    ]
+}
+
+/// Writes every value to its own entry and returns the log's bytes.
+fn write_log(samples: &[(u64, DecodedValue)]) -> Vec<u8> {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+
+    for (i, (timestamp, value)) in samples.iter().enumerate() {
+        let name = format!("entry{i}");
+        let timestamp = *timestamp;
+
+        match value.clone() {
+            DecodedValue::Raw(data) => writer
+                .new_bytes_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Boolean(data) => writer
+                .new_bool_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Int64(data) => writer
+                .new_i64_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Float(data) => writer
+                .new_f32_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Double(data) => writer
+                .new_f64_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::String(data) => writer
+                .new_string_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::BooleanArray(data) => writer
+                .new_bool_array_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(&data, timestamp),
+            DecodedValue::Int64Array(data) => writer
+                .new_i64_array_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(&data, timestamp),
+            DecodedValue::FloatArray(data) => writer
+                .new_f32_array_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(&data, timestamp),
+            DecodedValue::DoubleArray(data) => writer
+                .new_f64_array_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(&data, timestamp),
+            DecodedValue::StringArray(data) => {
+                let data = data.iter().map(String::as_str).collect::<Vec<_>>();
+                writer
+                    .new_string_array_entry(name, None)
+                    .unwrap()
+                    .update_with_timestamp(&data, timestamp)
+            }
+            DecodedValue::Json(data) => writer
+                .new_json_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Msgpack(data) => writer
+                .new_msgpack_entry(name, None)
+                .unwrap()
+                .update_with_timestamp(data, timestamp),
+            DecodedValue::Empty => writer
+                .make_entry(name, "double".into(), String::new())
+                .unwrap()
+                .log_data_with_timestamp(Box::new([]), timestamp),
+        }
+        .unwrap();
+    }
+
+    writer.join().unwrap()
+}
+
+/// Reads back every data record as `(timestamp, value)`, in file order.
+fn read_log(data: &[u8]) -> Vec<(u64, DecodedValue)> {
+    let mut types = HashMap::new();
+    let mut samples = vec![];
+
+    for record in WPILOGReader::new_raw(data).unwrap() {
+        let record: Record = record.try_into().unwrap();
+
+        match record.info {
+            RecordInfo::Control(ControlData::Start { r#type, .. }) => {
+                types.insert(record.id, WpiType::from_type_str(&r#type));
+            }
+            RecordInfo::Control(_) => {}
+            RecordInfo::Data(data) => {
+                let value = DecodedValue::decode(&types[&record.id], &data).unwrap();
+                samples.push((record.timestamp, value));
+            }
+        }
+    }
+
+    samples
+}
+
+proptest! {
+    #[test]
+    fn typed_updates_round_trip(samples in prop::collection::vec((any::<u64>(), value()), 0..32)) {
+        let data = write_log(&samples);
+
+        prop_assert_eq!(read_log(&data), samples);
+    }
+}
+
+#[test]
+fn non_finite_floats_round_trip() {
+    let doubles = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0];
+    let floats = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -0.0];
+
+    let mut samples = vec![];
+    for double in doubles {
+        samples.push((0, DecodedValue::Double(double)));
+    }
+    for float in floats {
+        samples.push((0, DecodedValue::Float(float)));
+    }
+    samples.push((0, DecodedValue::DoubleArray(doubles.to_vec())));
+    samples.push((0, DecodedValue::FloatArray(floats.to_vec())));
+
+    let decoded = read_log(&write_log(&samples));
+    assert_eq!(decoded.len(), samples.len());
+
+    // NaN != NaN, so compare the exact bits instead
+    let bits = |value: &DecodedValue| -> Vec<u64> {
+        match value {
+            DecodedValue::Double(double) => vec![double.to_bits()],
+            DecodedValue::Float(float) => vec![float.to_bits().into()],
+            DecodedValue::DoubleArray(doubles) => doubles.iter().map(|d| d.to_bits()).collect(),
+            DecodedValue::FloatArray(floats) => floats.iter().map(|f| f.to_bits().into()).collect(),
+            _ => unreachable!(),
+        }
+    };
+
+    for ((_, expected), (_, actual)) in samples.iter().zip(&decoded) {
+        assert_eq!(bits(expected), bits(actual));
+    }
+}
+
+#[test]
+fn finite_entry_rejects_non_finite() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let double = FiniteEntry(writer.new_f64_entry("double".into(), None).unwrap());
+    let doubles = FiniteEntry(writer.new_f64_array_entry("doubles".into(), None).unwrap());
+
+    assert!(double.update(f64::NAN).is_err());
+    assert!(double.update(f64::NEG_INFINITY).is_err());
+    assert!(doubles.update(&[0.0, f64::INFINITY]).is_err());
+    double.update(-0.0).unwrap();
+    doubles.update(&[1.0, -1.0]).unwrap();
+    drop((double, doubles));
+
+    let decoded = read_log(&writer.join().unwrap());
+    assert_eq!(
+        decoded,
+        [
+            (0, DecodedValue::Double(-0.0)),
+            (0, DecodedValue::DoubleArray(vec![1.0, -1.0]))
+        ]
+    );
+}
+
+#[test]
+fn update_returns_payload_size() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+
+    let double = writer.new_f64_entry("double".into(), None).unwrap();
+    let strings = writer
+        .new_string_array_entry("strings".into(), None)
+        .unwrap();
+    let raw = writer.new_bytes_entry("raw".into(), None).unwrap();
+
+    assert_eq!(double.update(1.0).unwrap(), 8);
+    assert_eq!(strings.update(&["ab", "c"]).unwrap(), 4 + 4 + 2 + 4 + 1);
+    assert_eq!(raw.update(Box::new([])).unwrap(), 0);
+}
+
+/// Claims `len` items but only has `items`, which `ExactSizeIterator` doesn't rule out.
+struct WrongLength {
+    items: std::vec::IntoIter<i64>,
+    len: usize,
+}
+
+impl Iterator for WrongLength {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        self.items.next()
+    }
+}
+
+impl ExactSizeIterator for WrongLength {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[test]
+fn update_iter_payload_matches_update() {
+    for pool in [None, Some(4)] {
+        let mut builder = WPILOGWriterBuilder::new();
+        if let Some(size) = pool {
+            builder = builder.buffer_pool(size);
+        }
+        let writer = builder.build(Vec::new(), NoopTimeProvider {}).unwrap();
+
+        let bools = writer.new_bool_array_entry("bools".into(), None).unwrap();
+        let ints = writer.new_i64_array_entry("ints".into(), None).unwrap();
+        let floats = writer.new_f32_array_entry("floats".into(), None).unwrap();
+        let doubles = writer.new_f64_array_entry("doubles".into(), None).unwrap();
+
+        bools.update(&[true, false, true]).unwrap();
+        assert_eq!(bools.update_iter([true, false, true]).unwrap(), 3);
+        ints.update(&[1, -2, i64::MAX]).unwrap();
+        assert_eq!(ints.update_iter(vec![1, -2, i64::MAX]).unwrap(), 24);
+        floats.update(&[0.5, -1.25]).unwrap();
+        assert_eq!(floats.update_iter([0.5, -1.25]).unwrap(), 8);
+        doubles.update(&[]).unwrap();
+        assert_eq!(doubles.update_iter(std::iter::empty()).unwrap(), 0);
+
+        // Too many items are cut off at the length, too few aren't logged at all
+        let long = WrongLength {
+            items: vec![7, 8, 9].into_iter(),
+            len: 2,
+        };
+        assert_eq!(ints.update_iter(long).unwrap(), 16);
+        let short = WrongLength {
+            items: vec![7].into_iter(),
+            len: 2,
+        };
+        assert!(matches!(
+            ints.update_iter(short),
+            Err(WpilogError::InvalidInput(_))
+        ));
+        drop((bools, ints, floats, doubles));
+
+        let data = writer.join().unwrap();
+        let payloads: Vec<(u32, Vec<u8>)> = WPILOGReader::new_raw(&data[..])
+            .unwrap()
+            .filter(|record| record.is_data())
+            .map(|record| (record.id, record.data.into_vec()))
+            .collect();
+        let le = |values: &[i64]| {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect()
+        };
+        let ints: Vec<u8> = le(&[1, -2, i64::MAX]);
+        let floats = [0.5f32.to_le_bytes(), (-1.25f32).to_le_bytes()].concat();
+
+        // Every pair of `update()` and `update_iter()` payloads is identical
+        assert_eq!(
+            payloads,
+            [
+                (1, vec![1, 0, 1]),
+                (1, vec![1, 0, 1]),
+                (2, ints.clone()),
+                (2, ints),
+                (3, floats.clone()),
+                (3, floats),
+                (4, vec![]),
+                (4, vec![]),
+                (2, le(&[7, 8])),
+            ]
+        );
+    }
+}
+
+#[test]
+fn strings_log_from_str_and_cow() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let status = writer.new_string_entry("status".into(), None).unwrap();
+
+    status.update_str_with_timestamp("idle", 1).unwrap();
+    status
+        .update_str_with_timestamp(std::borrow::Cow::Borrowed("running"), 2)
+        .unwrap();
+    status.update_with_timestamp("done".into(), 3).unwrap();
+
+    let data = writer.join().unwrap();
+    assert_eq!(
+        read_log(&data),
+        [
+            (1, DecodedValue::String("idle".into())),
+            (2, DecodedValue::String("running".into())),
+            (3, DecodedValue::String("done".into())),
+        ]
+    );
+}
+
+#[test]
+fn matrix_shape_round_trips() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let matrix = writer.new_matrix_entry("matrix".into(), &[2, 3]).unwrap();
+
+    assert!(matrix.update(&[1.0; 5]).is_err());
+    matrix.update(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+
+    let data = writer.join().unwrap();
+    let mut typed = WPILOGReader::new_raw(&data[..]).unwrap().typed();
+    let record = typed.next().unwrap().unwrap();
+    assert_eq!(
+        record.value,
+        DecodedValue::DoubleArray(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+    );
+
+    let info = typed.schema().entry(1).unwrap();
+    assert_eq!(info.r#type, WpiType::DoubleArray);
+    assert_eq!(info.shape(), Some(vec![2, 3]));
+    assert_eq!(
+        wpilog::value::shape_from_metadata(r#"{"unit": "m", "shape": [ 4 ]}"#),
+        Some(vec![4])
+    );
+}
+
+#[test]
+fn system_time_offset_from_entry() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let value = writer.new_i64_entry("value".into(), None).unwrap();
+    let system_time = writer
+        .new_i64_entry(wpilog::schema::SYSTEM_TIME_ENTRY.into(), None)
+        .unwrap();
+
+    value.update_with_timestamp(1, 100).unwrap();
+    system_time
+        .update_with_timestamp(1_700_000_000_000_000, 1_000)
+        .unwrap();
+    value.update_with_timestamp(2, 2_000).unwrap();
+
+    let data = writer.join().unwrap();
+    let mut typed = WPILOGReader::new_raw(&data[..])
+        .unwrap()
+        .schema()
+        .skip_system_time(true)
+        .typed();
+
+    let first = typed.next().unwrap().unwrap();
+    assert_eq!(first.value, DecodedValue::Int64(1));
+    assert_eq!(typed.schema().system_time_offset(), None);
+
+    let second = typed.next().unwrap().unwrap();
+    assert_eq!(second.value, DecodedValue::Int64(2));
+    assert_eq!(
+        typed.schema().system_time_offset(),
+        Some(1_700_000_000_000_000 - 1_000)
+    );
+    assert_eq!(
+        typed.schema().system_time(second.timestamp),
+        Some(1_700_000_000_001_000)
+    );
+    assert!(typed.next().is_none());
+}
+
+#[test]
+fn schema_reader_tracks_open_entries() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let value = writer.new_i64_entry("value".into(), None).unwrap();
+    value.update(1).unwrap();
+    value.raw_entry().finish().unwrap();
+
+    let data = writer.join().unwrap();
+    let mut schema = WPILOGReader::new_raw(&data[..]).unwrap().schema();
+    assert!(!schema.is_open(1));
+
+    let mut open = vec![];
+    while let Some(record) = schema.next() {
+        record.unwrap();
+        open.push(schema.is_open(1));
+    }
+
+    // Start, the value, then Finish
+    assert_eq!(open, [true, true, false]);
+}
@@ -0,0 +1,27 @@
+//! Session metadata in the extra header, which needs the `serde_json` feature.
+#![cfg(feature = "serde_json")]
+
+mod common;
+
+use common::NoopTimeProvider;
+use serde_json::json;
+use wpilog::{reader::WPILOGReader, writer::WPILOGWriterBuilder};
+
+#[test]
+fn session_metadata_round_trips() {
+    let session = json!({"event": "2024casj", "match": 12, "alliance": "red"});
+
+    let writer = WPILOGWriterBuilder::new()
+        .session_metadata(session.clone())
+        .wall_clock_start(true)
+        .build(Vec::new(), NoopTimeProvider {})
+        .unwrap();
+    let data = writer.join().unwrap();
+
+    let reader = WPILOGReader::new_raw(&data[..]).unwrap();
+    assert_eq!(
+        reader.session_metadata::<serde_json::Value>().unwrap(),
+        session
+    );
+    assert!(reader.wall_clock_start().is_some());
+}
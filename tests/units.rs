@@ -0,0 +1,46 @@
+//! Entries made with a unit, which needs the `serde_json` feature.
+#![cfg(feature = "serde_json")]
+
+mod common;
+
+use common::NoopTimeProvider;
+use serde_json::{json, Value};
+use wpilog::{entrytypes::LogWriter, reader::WPILOGReader, writer::WPILOGWriter};
+
+#[test]
+fn unit_is_in_the_metadata() {
+    let writer = WPILOGWriter::new(Vec::new(), NoopTimeProvider {});
+    let distance = writer
+        .new_f64_entry_with_unit("distance".into(), Some("meters"))
+        .unwrap();
+    let ticks = writer
+        .new_i32_entry_with_unit("ticks".into(), Some("encoder \"ticks\""))
+        .unwrap();
+    let speeds = writer
+        .new_f64_array_entry_with_unit("speeds".into(), Some("m/s"))
+        .unwrap();
+    let count = writer
+        .new_i64_entry_with_unit("count".into(), None)
+        .unwrap();
+    drop((distance, ticks, speeds, count));
+
+    let data = writer.join().unwrap();
+    let mut schema = WPILOGReader::new_raw(&data[..]).unwrap().schema();
+    while schema.next().is_some() {}
+
+    let metadata = |id| {
+        let entry = schema.entry(id).unwrap();
+        (entry.name.to_string(), entry.metadata_as::<Value>().ok())
+    };
+    assert_eq!(
+        (1..=4).map(metadata).collect::<Vec<_>>(),
+        [
+            ("distance".into(), Some(json!({"unit": "meters"}))),
+            ("ticks".into(), Some(json!({"unit": "encoder \"ticks\""}))),
+            ("speeds".into(), Some(json!({"unit": "m/s"}))),
+            // No unit means no metadata at all, like a normal constructor without any
+            ("count".into(), None),
+        ]
+    );
+    assert_eq!(&*schema.entry(4).unwrap().metadata, "");
+}
@@ -0,0 +1,149 @@
+//! Every kind of problem `validate` reports, in hand made logs.
+
+use wpilog::{
+    reader::{ReaderOptions, WPILOGReader},
+    validate::{validate, Issue, ValidationReport},
+};
+
+/// A record with one byte each for the id, payload size and timestamp.
+fn record(id: u8, timestamp: u8, payload: &[u8]) -> Vec<u8> {
+    let mut record = vec![0, id, u8::try_from(payload.len()).unwrap(), timestamp];
+    record.extend_from_slice(payload);
+    record
+}
+
+/// A length prefixed string, like in control records.
+fn string(string: &str) -> Vec<u8> {
+    let mut bytes = u32::try_from(string.len()).unwrap().to_le_bytes().to_vec();
+    bytes.extend_from_slice(string.as_bytes());
+    bytes
+}
+
+fn start(id: u8, timestamp: u8, name: &str, r#type: &str) -> Vec<u8> {
+    let mut payload = vec![0, id, 0, 0, 0];
+    payload.extend(string(name));
+    payload.extend(string(r#type));
+    payload.extend(string(""));
+    record(0, timestamp, &payload)
+}
+
+fn log(records: &[Vec<u8>]) -> Vec<u8> {
+    let mut log = b"WPILOG\x00\x01\x00\x00\x00\x00".to_vec();
+    for record in records {
+        log.extend_from_slice(record);
+    }
+    log
+}
+
+fn validate_log(data: &[u8], options: &ReaderOptions) -> ValidationReport {
+    validate(&mut WPILOGReader::with_options(data, options).unwrap())
+}
+
+#[test]
+fn reports_every_issue() {
+    let mut metadata = vec![2, 2, 0, 0, 0];
+    metadata.extend(string("{}"));
+
+    let data = log(&[
+        start(1, 10, "count", "int64"),
+        record(1, 20, &5i64.to_le_bytes()),
+        record(1, 30, &[]),
+        start(1, 30, "count", "int64"),
+        record(2, 30, &[1]),
+        record(0, 40, &[1, 2, 0, 0, 0]),
+        record(0, 40, &metadata),
+        record(0, 40, &[9]),
+        record(1, 35, &[0; 4]),
+        // Claims 8 bytes but the log ends after 3
+        vec![0, 1, 8, 50, 1, 2, 3],
+    ]);
+
+    let report = validate_log(&data, &ReaderOptions::default());
+    assert_eq!(report.records, 9);
+
+    let issues: Vec<_> = report
+        .issues
+        .into_iter()
+        .map(|issue| match issue {
+            // The messages are for people, only check that they're there
+            Issue::InvalidControl { timestamp, message } => {
+                assert!(!message.is_empty());
+                Issue::InvalidControl {
+                    timestamp,
+                    message: String::new(),
+                }
+            }
+            Issue::Truncated { message } => {
+                assert!(!message.is_empty());
+                Issue::Truncated {
+                    message: String::new(),
+                }
+            }
+            issue => issue,
+        })
+        .collect();
+
+    assert_eq!(
+        issues,
+        [
+            Issue::InvalidPayload {
+                id: 1,
+                timestamp: 30,
+                message: "int64 payload must be 8 bytes, got 0".into()
+            },
+            Issue::DuplicateStart {
+                id: 1,
+                timestamp: 30
+            },
+            Issue::UnknownEntry {
+                id: 2,
+                timestamp: 30
+            },
+            Issue::FinishWithoutStart {
+                id: 2,
+                timestamp: 40
+            },
+            Issue::MetadataWithoutStart {
+                id: 2,
+                timestamp: 40
+            },
+            Issue::InvalidControl {
+                timestamp: 40,
+                message: String::new()
+            },
+            Issue::NonMonotonicTimestamp {
+                previous: 40,
+                timestamp: 35
+            },
+            Issue::InvalidPayload {
+                id: 1,
+                timestamp: 35,
+                message: "int64 payload must be 8 bytes, got 4".into()
+            },
+            Issue::Truncated {
+                message: String::new()
+            },
+        ]
+    );
+}
+
+#[test]
+fn oversized_record_is_invalid_not_truncated() {
+    let data = log(&[
+        start(1, 10, "raw", "raw"),
+        record(1, 20, &[0; 16]),
+        record(1, 30, &[0; 100]),
+    ]);
+    let options = ReaderOptions {
+        max_record_size: 64,
+        ..ReaderOptions::default()
+    };
+
+    let report = validate_log(&data, &options);
+    assert_eq!(report.records, 2);
+    assert!(
+        matches!(&report.issues[..], [Issue::InvalidRecord { message }] if message.contains("limit")),
+        "{:?}",
+        report.issues
+    );
+}
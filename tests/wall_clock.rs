@@ -0,0 +1,49 @@
+//! Converting timestamps to wall clock time, which needs the `chrono` feature.
+#![cfg(feature = "chrono")]
+
+mod common;
+
+use common::ManualClock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wpilog::{reader::WPILOGReader, writer::WPILOGWriterBuilder};
+
+#[test]
+fn wall_clock_round_trips() {
+    let clock = ManualClock::default();
+    clock.set(5_000_000);
+
+    let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let writer = WPILOGWriterBuilder::new()
+        .extra_header("team=1234")
+        .wall_clock_start(true)
+        .build(Vec::new(), clock)
+        .unwrap();
+    let after = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let data = writer.join().unwrap();
+
+    let reader = WPILOGReader::new_raw(&data[..]).unwrap();
+    assert!(reader.extra_header.starts_with(b"team=1234\n"));
+
+    let (unix, start) = reader.wall_clock_start().unwrap();
+    assert_eq!(start, 5_000_000);
+    assert!((before.as_micros()..=after.as_micros()).contains(&u128::from(unix)));
+
+    // One second of log time later is one second of wall clock time later
+    let later = reader.wall_clock(6_000_000).unwrap();
+    assert_eq!(
+        later.timestamp_micros(),
+        i64::try_from(unix).unwrap() + 1_000_000
+    );
+}
+
+#[test]
+fn overflowing_header_is_none() {
+    let mut data = b"WPILOG\x00\x01".to_vec();
+    let header = format!("wall_clock_start={}@0", i64::MAX);
+    data.extend_from_slice(&u32::try_from(header.len()).unwrap().to_le_bytes());
+    data.extend_from_slice(header.as_bytes());
+
+    let reader = WPILOGReader::new_raw(&data[..]).unwrap();
+    assert_eq!(reader.wall_clock(1), None);
+    assert_eq!(reader.wall_clock(0), None);
+}